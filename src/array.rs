@@ -0,0 +1,334 @@
+use core::borrow::Borrow;
+use core::error::Error;
+use core::fmt;
+use core::mem;
+
+use crate::{InsertConflict, Overwritten, PairDebug};
+
+/// The error returned by [`ArrayBiMap::insert`] when a new pair does not fit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityError<L, R> {
+    pub left: L,
+    pub right: R,
+}
+
+impl<L, R> CapacityError<L, R> {
+    #[inline(always)]
+    pub fn into_pair(self) -> (L, R) {
+        (self.left, self.right)
+    }
+}
+
+impl<L, R> fmt::Display for CapacityError<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bimap is at capacity")
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> Error for CapacityError<L, R> {}
+
+/// The error returned by [`ArrayBiMap::try_insert`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryInsertError<'a, L, R> {
+    /// Either value is already bound.
+    Conflict(InsertConflict<'a, L, R>),
+    /// Neither value is bound, but the map is full.
+    Capacity(CapacityError<L, R>),
+}
+
+impl<L, R> TryInsertError<'_, L, R> {
+    #[inline(always)]
+    pub fn into_pair(self) -> (L, R) {
+        match self {
+            TryInsertError::Conflict(conflict) => conflict.into_pair(),
+            TryInsertError::Capacity(error) => error.into_pair(),
+        }
+    }
+}
+
+impl<L, R> fmt::Display for TryInsertError<'_, L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInsertError::Conflict(conflict) => conflict.fmt(f),
+            TryInsertError::Capacity(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> Error for TryInsertError<'_, L, R> {}
+
+/// A bimap holding at most `N` pairs inline, without ever allocating. Lookups
+/// are linear scans, which beat hashing at the sizes this is meant for. Pairs
+/// occupy the first `len` slots; removal moves the last pair into the gap.
+#[derive(Clone)]
+pub struct ArrayBiMap<L, R, const N: usize> {
+    pairs: [Option<(L, R)>; N],
+    len: usize,
+}
+
+impl<L, R, const N: usize> ArrayBiMap<L, R, N> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            pairs: [const { None }; N],
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline(always)]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        for pair in &mut self.pairs[..self.len] {
+            *pair = None;
+        }
+        self.len = 0;
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&L, &R)> + ExactSizeIterator {
+        (0..self.len).map(|slot| self.pair(slot))
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl DoubleEndedIterator<Item = &L> + ExactSizeIterator {
+        self.iter().map(|(left, _)| left)
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl DoubleEndedIterator<Item = &R> + ExactSizeIterator {
+        self.iter().map(|(_, right)| right)
+    }
+
+    /// Keeps only the pairs for which `f` returns `true`.
+    #[inline(always)]
+    pub fn retain<F: FnMut(&L, &R) -> bool>(&mut self, mut f: F) {
+        let mut slot = 0;
+        while slot < self.len {
+            let (left, right) = self.pairs[slot].as_ref().unwrap();
+            if f(left, right) {
+                slot += 1;
+            } else {
+                self.swap_remove(slot);
+            }
+        }
+    }
+
+    /// Removes every pair, yielding them by value. The map is empty afterwards
+    /// even if the iterator is dropped early.
+    #[inline(always)]
+    pub fn drain(&mut self) -> impl Iterator<Item = (L, R)> {
+        let len = mem::take(&mut self.len);
+        mem::replace(&mut self.pairs, [const { None }; N])
+            .into_iter()
+            .take(len)
+            .map(Option::unwrap)
+    }
+
+    /// Removes and yields the pairs for which `pred` returns `true`. Pairs not
+    /// yet visited when the iterator is dropped stay in the map.
+    #[inline(always)]
+    pub fn extract_if<F: FnMut(&L, &R) -> bool>(
+        &mut self,
+        mut pred: F,
+    ) -> impl Iterator<Item = (L, R)> {
+        let mut slot = 0;
+        core::iter::from_fn(move || {
+            while slot < self.len {
+                let (left, right) = self.pair(slot);
+                if pred(left, right) {
+                    return Some(self.swap_remove(slot));
+                }
+                slot += 1;
+            }
+            None
+        })
+    }
+
+    #[inline(always)]
+    fn pair(&self, slot: usize) -> (&L, &R) {
+        let (left, right) = self.pairs[slot].as_ref().unwrap();
+        (left, right)
+    }
+
+    #[inline(always)]
+    fn position(&self, mut pred: impl FnMut(&(L, R)) -> bool) -> Option<usize> {
+        self.pairs[..self.len]
+            .iter()
+            .position(|pair| pred(pair.as_ref().unwrap()))
+    }
+
+    #[inline(always)]
+    fn swap_remove(&mut self, slot: usize) -> (L, R) {
+        self.len -= 1;
+        self.pairs.swap(slot, self.len);
+        self.pairs[self.len].take().unwrap()
+    }
+}
+
+impl<L: Eq, R: Eq, const N: usize> ArrayBiMap<L, R, N> {
+    /// Like [`BiMap::insert`](crate::BiMap::insert), but fails if the pair
+    /// would need a new slot and the map is full. Displacing existing pairs
+    /// never needs one, so that succeeds even at capacity.
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Result<Overwritten<L, R>, CapacityError<L, R>> {
+        let by_left = self.position(|pair| pair.0 == left);
+        let by_right = self.position(|pair| pair.1 == right);
+        let (slot, overwritten) = match (by_left, by_right) {
+            (None, None) => {
+                if self.is_full() {
+                    return Err(CapacityError { left, right });
+                }
+                self.len += 1;
+                (self.len - 1, Overwritten::Neither)
+            }
+            (Some(i), Some(j)) if i == j => {
+                let (old_left, old_right) = self.pairs[i].take().unwrap();
+                (i, Overwritten::Pair(old_left, old_right))
+            }
+            (Some(i), None) => {
+                let (old_left, old_right) = self.pairs[i].take().unwrap();
+                (i, Overwritten::Left(old_left, old_right))
+            }
+            (None, Some(j)) => {
+                let (old_left, old_right) = self.pairs[j].take().unwrap();
+                (j, Overwritten::Right(old_left, old_right))
+            }
+            (Some(i), Some(j)) => {
+                // Remove the higher slot first so the lower one stays in place.
+                let (by_left, by_right) = if i > j {
+                    let by_left = self.swap_remove(i);
+                    (by_left, self.swap_remove(j))
+                } else {
+                    let by_right = self.swap_remove(j);
+                    (self.swap_remove(i), by_right)
+                };
+                self.len += 1;
+                (self.len - 1, Overwritten::Both(by_left, by_right))
+            }
+        };
+        self.pairs[slot] = Some((left, right));
+        Ok(overwritten)
+    }
+
+    /// Inserts the pair only if neither value is bound and there is room for
+    /// it, otherwise hands it back.
+    #[inline(always)]
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), TryInsertError<'_, L, R>> {
+        let by_left = self.position(|pair| pair.0 == left);
+        let by_right = self.position(|pair| pair.1 == right);
+        if by_left.is_some() || by_right.is_some() {
+            return Err(TryInsertError::Conflict(InsertConflict {
+                left,
+                right,
+                existing_left: by_left.map(|slot| self.pair(slot)),
+                existing_right: by_right.map(|slot| self.pair(slot)),
+            }));
+        }
+        if self.is_full() {
+            return Err(TryInsertError::Capacity(CapacityError { left, right }));
+        }
+
+        self.pairs[self.len] = Some((left, right));
+        self.len += 1;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Eq>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        let slot = self.position(|pair| pair.0.borrow() == left)?;
+        Some(&self.pairs[slot].as_ref().unwrap().1)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Eq>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+    {
+        let slot = self.position(|pair| pair.1.borrow() == right)?;
+        Some(&self.pairs[slot].as_ref().unwrap().0)
+    }
+
+    #[inline(always)]
+    pub fn remove_left<Q: ?Sized + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let slot = self.position(|pair| pair.0.borrow() == left)?;
+        Some(self.swap_remove(slot))
+    }
+
+    #[inline(always)]
+    pub fn remove_right<Q: ?Sized + Eq>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let slot = self.position(|pair| pair.1.borrow() == right)?;
+        Some(self.swap_remove(slot))
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.get_left(left).is_some()
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.get_right(right).is_some()
+    }
+}
+
+impl<L, R, const N: usize> Default for ArrayBiMap<L, R, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Eq, R: Eq, const N: usize> PartialEq for ArrayBiMap<L, R, N> {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(left, right)| other.get_left(left) == Some(right))
+    }
+}
+
+impl<L: Eq, R: Eq, const N: usize> Eq for ArrayBiMap<L, R, N> {}
+
+/// Formats as `{left <-> right, ...}`.
+impl<L: fmt::Debug, R: fmt::Debug, const N: usize> fmt::Debug for ArrayBiMap<L, R, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|(left, right)| PairDebug(left, right)))
+            .finish()
+    }
+}
@@ -0,0 +1,406 @@
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+
+use crate::{InsertConflict, Overwritten, PairDebug};
+
+/// Something that exposes a borrowed key `Q`. Both indexes and lookup probes
+/// are searched as `dyn Key<Q>`, which lets the sets be queried by any `&Q`
+/// the side borrows as without a `Borrow<Q>` impl on the index entry itself.
+trait Key<Q: ?Sized> {
+    fn key(&self) -> &Q;
+}
+
+/// A borrowed lookup key.
+struct Probe<'a, Q: ?Sized>(&'a Q);
+
+impl<Q: ?Sized> Key<Q> for Probe<'_, Q> {
+    #[inline(always)]
+    fn key(&self) -> &Q {
+        self.0
+    }
+}
+
+impl<Q: ?Sized + Ord> PartialEq for dyn Key<Q> + '_ {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<Q: ?Sized + Ord> Eq for dyn Key<Q> + '_ {}
+
+impl<Q: ?Sized + Ord> PartialOrd for dyn Key<Q> + '_ {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Q: ?Sized + Ord> Ord for dyn Key<Q> + '_ {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+/// Defines an index entry that orders a shared pair by one of its values.
+macro_rules! index_entry {
+    ($name:ident, $side:ident, $field:tt) => {
+        struct $name<L, R>(Arc<(L, R)>);
+
+        impl<L, R> $name<L, R> {
+            #[inline(always)]
+            fn pair(&self) -> (&L, &R) {
+                (&self.0.0, &self.0.1)
+            }
+        }
+
+        impl<L, R, Q: ?Sized> Key<Q> for $name<L, R>
+        where
+            $side: Borrow<Q>,
+        {
+            #[inline(always)]
+            fn key(&self) -> &Q {
+                self.0.$field.borrow()
+            }
+        }
+
+        impl<'a, L: 'a, R: 'a, Q: ?Sized + 'a> Borrow<dyn Key<Q> + 'a> for $name<L, R>
+        where
+            $side: Borrow<Q>,
+        {
+            #[inline(always)]
+            fn borrow(&self) -> &(dyn Key<Q> + 'a) {
+                self
+            }
+        }
+
+        impl<L, R> PartialEq for $name<L, R>
+        where
+            $side: Ord,
+        {
+            #[inline(always)]
+            fn eq(&self, other: &Self) -> bool {
+                self.0.$field == other.0.$field
+            }
+        }
+
+        impl<L, R> Eq for $name<L, R> where $side: Ord {}
+
+        impl<L, R> PartialOrd for $name<L, R>
+        where
+            $side: Ord,
+        {
+            #[inline(always)]
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<L, R> Ord for $name<L, R>
+        where
+            $side: Ord,
+        {
+            #[inline(always)]
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.$field.cmp(&other.0.$field)
+            }
+        }
+    };
+}
+
+index_entry!(ByLeft, L, 0);
+index_entry!(ByRight, R, 1);
+
+/// Turns a bound on `Q` into a bound on the probe type the sets search by.
+#[inline(always)]
+fn probe_bound<'a, Q: ?Sized>(bound: &'a Bound<Probe<'_, Q>>) -> Bound<&'a (dyn Key<Q> + 'a)> {
+    bound.as_ref().map(|probe| probe as &dyn Key<Q>)
+}
+
+/// A bimap backed by ordered sets on both sides, iterating in sorted order.
+/// Each pair is stored once and shared by the two sets, so neither side has
+/// to be `Clone`. Sharing goes through `Arc`, so the type only exists on
+/// targets with atomic pointers.
+pub struct BTreeBiMap<L, R> {
+    by_left: BTreeSet<ByLeft<L, R>>,
+    by_right: BTreeSet<ByRight<L, R>>,
+}
+
+impl<L, R> BTreeBiMap<L, R> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            by_left: BTreeSet::new(),
+            by_right: BTreeSet::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.by_left.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.by_left.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.by_left.clear();
+        self.by_right.clear();
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl DoubleEndedIterator<Item = &L> + ExactSizeIterator {
+        self.by_left.iter().map(|entry| &entry.0.0)
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl DoubleEndedIterator<Item = &R> + ExactSizeIterator {
+        self.by_right.iter().map(|entry| &entry.0.1)
+    }
+
+    /// Iterates over the pairs in ascending order of their left values.
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&L, &R)> + ExactSizeIterator {
+        self.by_left.iter().map(ByLeft::pair)
+    }
+
+    /// Iterates over the pairs in ascending order of their right values.
+    #[inline(always)]
+    pub fn iter_by_right(&self) -> impl DoubleEndedIterator<Item = (&L, &R)> + ExactSizeIterator {
+        self.by_right.iter().map(ByRight::pair)
+    }
+}
+
+impl<L: Ord, R: Ord> BTreeBiMap<L, R> {
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let by_left = self.remove_left(&left);
+        let by_right = self.remove_right(&right);
+        let is_pair = matches!(&by_left, Some((_, old_right)) if old_right == &right);
+        self.link(left, right);
+
+        match (by_left, by_right) {
+            (None, None) => Overwritten::Neither,
+            (Some(pair), None) if is_pair => Overwritten::Pair(pair.0, pair.1),
+            (Some(pair), None) => Overwritten::Left(pair.0, pair.1),
+            (None, Some(pair)) => Overwritten::Right(pair.0, pair.1),
+            (Some(by_left), Some(by_right)) => Overwritten::Both(by_left, by_right),
+        }
+    }
+
+    #[inline(always)]
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        if self.contains_left(&left) || self.contains_right(&right) {
+            return Err(InsertConflict {
+                existing_left: self.get_pair_by_left(&left),
+                existing_right: self.get_pair_by_right(&right),
+                left,
+                right,
+            });
+        }
+
+        self.link(left, right);
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Ord>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        Some(self.get_pair_by_left(left)?.1)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Ord>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+    {
+        Some(self.get_pair_by_right(right)?.0)
+    }
+
+    #[inline(always)]
+    pub fn remove_left<Q: ?Sized + Ord>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let ByLeft(pair) = self.by_left.take(&Probe(left) as &dyn Key<Q>)?;
+        self.by_right.take(&Probe(&pair.1) as &dyn Key<R>);
+        Some(Self::unshare(pair))
+    }
+
+    #[inline(always)]
+    pub fn remove_right<Q: ?Sized + Ord>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let ByRight(pair) = self.by_right.take(&Probe(right) as &dyn Key<Q>)?;
+        self.by_left.take(&Probe(&pair.0) as &dyn Key<L>);
+        Some(Self::unshare(pair))
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Ord>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.by_left.contains(&Probe(left) as &dyn Key<Q>)
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Ord>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.by_right.contains(&Probe(right) as &dyn Key<Q>)
+    }
+
+    /// Iterates over the pairs whose left value is in `range`, ordered by left.
+    #[inline(always)]
+    pub fn range_left<Q: ?Sized + Ord, B: RangeBounds<Q>>(
+        &self,
+        range: B,
+    ) -> impl DoubleEndedIterator<Item = (&L, &R)>
+    where
+        L: Borrow<Q>,
+    {
+        let start = range.start_bound().map(Probe);
+        let end = range.end_bound().map(Probe);
+        self.by_left
+            .range::<dyn Key<Q>, _>((probe_bound(&start), probe_bound(&end)))
+            .map(ByLeft::pair)
+    }
+
+    /// Iterates over the pairs whose right value is in `range`, ordered by right.
+    #[inline(always)]
+    pub fn range_right<Q: ?Sized + Ord, B: RangeBounds<Q>>(
+        &self,
+        range: B,
+    ) -> impl DoubleEndedIterator<Item = (&L, &R)>
+    where
+        R: Borrow<Q>,
+    {
+        let start = range.start_bound().map(Probe);
+        let end = range.end_bound().map(Probe);
+        self.by_right
+            .range::<dyn Key<Q>, _>((probe_bound(&start), probe_bound(&end)))
+            .map(ByRight::pair)
+    }
+
+    #[inline(always)]
+    pub fn first_left(&self) -> Option<(&L, &R)> {
+        self.by_left.first().map(ByLeft::pair)
+    }
+
+    #[inline(always)]
+    pub fn last_left(&self) -> Option<(&L, &R)> {
+        self.by_left.last().map(ByLeft::pair)
+    }
+
+    #[inline(always)]
+    pub fn first_right(&self) -> Option<(&L, &R)> {
+        self.by_right.first().map(ByRight::pair)
+    }
+
+    #[inline(always)]
+    pub fn last_right(&self) -> Option<(&L, &R)> {
+        self.by_right.last().map(ByRight::pair)
+    }
+
+    #[inline(always)]
+    fn get_pair_by_left<Q: ?Sized + Ord>(&self, left: &Q) -> Option<(&L, &R)>
+    where
+        L: Borrow<Q>,
+    {
+        self.by_left
+            .get(&Probe(left) as &dyn Key<Q>)
+            .map(ByLeft::pair)
+    }
+
+    #[inline(always)]
+    fn get_pair_by_right<Q: ?Sized + Ord>(&self, right: &Q) -> Option<(&L, &R)>
+    where
+        R: Borrow<Q>,
+    {
+        self.by_right
+            .get(&Probe(right) as &dyn Key<Q>)
+            .map(ByRight::pair)
+    }
+
+    /// Adds a pair whose values are both known to be unbound.
+    #[inline(always)]
+    fn link(&mut self, left: L, right: R) {
+        let pair = Arc::new((left, right));
+        self.by_left.insert(ByLeft(pair.clone()));
+        self.by_right.insert(ByRight(pair));
+    }
+
+    /// Takes back a pair that has been removed from both sets.
+    #[inline(always)]
+    fn unshare(pair: Arc<(L, R)>) -> (L, R) {
+        // Pairs are never shared beyond the map's own two sets.
+        Arc::into_inner(pair).unwrap()
+    }
+}
+
+impl<L, R> Default for BTreeBiMap<L, R> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clones every pair, so the clone shares nothing with the original.
+impl<L: Ord + Clone, R: Ord + Clone> Clone for BTreeBiMap<L, R> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        self.iter()
+            .map(|(left, right)| (left.clone(), right.clone()))
+            .collect()
+    }
+}
+
+impl<L: Ord, R: Ord> FromIterator<(L, R)> for BTreeBiMap<L, R> {
+    #[inline(always)]
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<L: Ord, R: Ord> Extend<(L, R)> for BTreeBiMap<L, R> {
+    #[inline(always)]
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+impl<L: PartialEq, R: PartialEq> PartialEq for BTreeBiMap<L, R> {
+    /// Both maps iterate in left order, so equal maps yield equal sequences.
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<L: Eq, R: Eq> Eq for BTreeBiMap<L, R> {}
+
+/// Formats as `{left <-> right, ...}` in ascending order of left values.
+impl<L: fmt::Debug, R: fmt::Debug> fmt::Debug for BTreeBiMap<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|(left, right)| PairDebug(left, right)))
+            .finish()
+    }
+}
@@ -0,0 +1,173 @@
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::BiMap;
+
+/// A borrowed view of a [`BiMap`] with its left and right sides swapped.
+pub struct Inverse<'a, L, R, LS, RS> {
+    map: &'a BiMap<L, R, LS, RS>,
+}
+
+/// A lazy view of the composition of a `BiMap<L, R>` with a `BiMap<R, C>`,
+/// mapping `L <-> C` through the shared middle values.
+pub struct Composed<'a, L, R, C, LS, RS, RS2, CS> {
+    first: &'a BiMap<L, R, LS, RS>,
+    second: &'a BiMap<R, C, RS2, CS>,
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> BiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn inverse(&self) -> Inverse<'_, L, R, LS, RS> {
+        Inverse { map: self }
+    }
+
+    /// Builds the map `L <-> C` holding every `(l, c)` for which this map has
+    /// `(l, r)` and `other` has `(r, c)`.
+    #[inline(always)]
+    pub fn compose<C, RS2, CS>(&self, other: &BiMap<R, C, RS2, CS>) -> BiMap<L, C, LS, CS>
+    where
+        L: Clone,
+        C: Eq + Hash + Clone,
+        LS: Clone,
+        RS2: BuildHasher,
+        CS: BuildHasher + Clone,
+    {
+        let mut composed =
+            BiMap::with_hashers(self.left_hasher().clone(), other.right_hasher().clone());
+        for (left, middle) in self.iter() {
+            if let Some(right) = other.get_left(middle) {
+                // Both inputs are one-to-one, so no pair can collide.
+                composed.push_new(left.clone(), right.clone());
+            }
+        }
+        composed
+    }
+
+    #[inline(always)]
+    pub fn compose_view<'a, C, RS2, CS>(
+        &'a self,
+        other: &'a BiMap<R, C, RS2, CS>,
+    ) -> Composed<'a, L, R, C, LS, RS, RS2, CS> {
+        Composed {
+            first: self,
+            second: other,
+        }
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> Inverse<'a, L, R, LS, RS> {
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&'a L>
+    where
+        R: Borrow<Q>,
+    {
+        self.map.get_right(left)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<&'a R>
+    where
+        L: Borrow<Q>,
+    {
+        self.map.get_left(right)
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.map.contains_right(left)
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.map.contains_left(right)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl Iterator<Item = &'a R> {
+        self.map.right_values()
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl Iterator<Item = &'a L> {
+        self.map.left_values()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&'a R, &'a L)> {
+        self.map.iter().map(|(left, right)| (right, left))
+    }
+}
+
+impl<'a, L, R, C, LS, RS, RS2, CS> Composed<'a, L, R, C, LS, RS, RS2, CS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    C: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+    RS2: BuildHasher,
+    CS: BuildHasher,
+{
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&'a C>
+    where
+        L: Borrow<Q>,
+    {
+        self.second.get_left(self.first.get_left(left)?)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<&'a L>
+    where
+        C: Borrow<Q>,
+    {
+        self.first.get_right(self.second.get_right(right)?)
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.get_left(left).is_some()
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        C: Borrow<Q>,
+    {
+        self.get_right(right).is_some()
+    }
+
+    /// Iterates over the joined pairs. Runs in time linear in the first map.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&'a L, &'a C)> {
+        let second = self.second;
+        self.first
+            .iter()
+            .filter_map(move |(left, middle)| Some((left, second.get_left(middle)?)))
+    }
+
+    /// Counts the joined pairs. Runs in time linear in the first map.
+    #[inline(always)]
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+}
@@ -0,0 +1,451 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use std::sync::{RwLock, RwLockWriteGuard};
+use std::thread;
+
+use hashbrown::HashTable;
+
+use crate::{DefaultHashBuilder, Overwritten};
+
+type Shard<K, V> = RwLock<HashTable<(K, V)>>;
+
+const POISONED: &str = "a ConcurrentBiMap shard was poisoned by a panic during an update";
+
+/// A thread-safe bimap with lock striping.
+///
+/// Each side is split into shards guarded by their own lock. A write locks
+/// every shard holding a value it touches on both sides, always in the same
+/// global order, so the two directions never disagree and writers that touch
+/// different shards never contend.
+///
+/// # Panics
+///
+/// If a `Hash` or `Eq` impl panics while an update holds shard locks, the
+/// update may have been applied to only one side. The locks it held are then
+/// poisoned, and every later operation that touches them panics instead of
+/// reading the inconsistent shards.
+pub struct ConcurrentBiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    left_shards: Box<[Shard<L, R>]>,
+    right_shards: Box<[Shard<R, L>]>,
+    left_hasher: LS,
+    right_hasher: RS,
+}
+
+/// The write guards held by one operation, keyed by shard index.
+struct Locked<'a, K, V> {
+    indices: Vec<usize>,
+    guards: Vec<RwLockWriteGuard<'a, HashTable<(K, V)>>>,
+}
+
+impl<'a, K, V> Locked<'a, K, V> {
+    #[inline(always)]
+    fn acquire(shards: &'a [Shard<K, V>], wanted: &[Option<usize>]) -> Self {
+        let mut indices: Vec<usize> = wanted.iter().flatten().copied().collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let guards = indices
+            .iter()
+            .map(|&i| shards[i].write().expect(POISONED))
+            .collect();
+        Self { indices, guards }
+    }
+
+    #[inline(always)]
+    fn covers(&self, shard: usize) -> bool {
+        self.indices.contains(&shard)
+    }
+
+    #[inline(always)]
+    fn table(&mut self, shard: usize) -> &mut HashTable<(K, V)> {
+        let position = self.indices.iter().position(|&i| i == shard).unwrap();
+        &mut self.guards[position]
+    }
+}
+
+impl<L, R> ConcurrentBiMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        let shards = thread::available_parallelism().map_or(1, usize::from) * 4;
+        Self::with_shard_amount(shards)
+    }
+
+    #[inline(always)]
+    pub fn with_shard_amount(shards: usize) -> Self {
+        Self::with_shard_amount_and_hashers(
+            shards,
+            DefaultHashBuilder::default(),
+            DefaultHashBuilder::default(),
+        )
+    }
+}
+
+impl<L, R, LS, RS> ConcurrentBiMap<L, R, LS, RS> {
+    /// Creates a map with `shards` locks per side, rounded up to a power of two.
+    #[inline(always)]
+    pub fn with_shard_amount_and_hashers(shards: usize, left_hasher: LS, right_hasher: RS) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+        Self {
+            left_shards: (0..shards).map(|_| RwLock::new(HashTable::new())).collect(),
+            right_shards: (0..shards).map(|_| RwLock::new(HashTable::new())).collect(),
+            left_hasher,
+            right_hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub fn shard_amount(&self) -> usize {
+        self.left_shards.len()
+    }
+
+    #[inline(always)]
+    fn shard(&self, hash: u64) -> usize {
+        // The low bits pick buckets and the top seven bits are tags inside
+        // each table, so take the shard from the bits in between.
+        (hash >> 32) as usize & (self.left_shards.len() - 1)
+    }
+
+    #[inline(always)]
+    fn lock(
+        &self,
+        lefts: &[Option<usize>],
+        rights: &[Option<usize>],
+    ) -> (Locked<'_, L, R>, Locked<'_, R, L>) {
+        // Left shards always come before right shards in the lock order.
+        let lefts = Locked::acquire(&self.left_shards, lefts);
+        (lefts, Locked::acquire(&self.right_shards, rights))
+    }
+}
+
+impl<L, R, LS, RS> ConcurrentBiMap<L, R, LS, RS>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    #[inline(always)]
+    pub fn insert(&self, left: L, right: R) -> Overwritten<L, R> {
+        let left_hash = self.left_hasher.hash_one(&left);
+        let right_hash = self.right_hasher.hash_one(&right);
+        let (left_shard, right_shard) = (self.shard(left_hash), self.shard(right_hash));
+        let (mut old_left_shard, mut old_right_shard) = (None, None);
+        loop {
+            let (mut lefts, mut rights) = self.lock(
+                &[Some(left_shard), old_left_shard],
+                &[Some(right_shard), old_right_shard],
+            );
+            old_right_shard = lefts
+                .table(left_shard)
+                .find(left_hash, |(l, _)| *l == left)
+                .map(|(_, r)| self.shard(self.right_hasher.hash_one(r)));
+            old_left_shard = rights
+                .table(right_shard)
+                .find(right_hash, |(r, _)| *r == right)
+                .map(|(_, l)| self.shard(self.left_hasher.hash_one(l)));
+            if !old_left_shard.is_none_or(|shard| lefts.covers(shard))
+                || !old_right_shard.is_none_or(|shard| rights.covers(shard))
+            {
+                continue;
+            }
+
+            let by_left = self.take_left(&mut lefts, left_hash, &left);
+            if let Some((_, old_right)) = &by_left {
+                self.take_right(
+                    &mut rights,
+                    self.right_hasher.hash_one(old_right),
+                    old_right,
+                );
+            }
+            let by_right = self
+                .take_right(&mut rights, right_hash, &right)
+                .map(|(old_right, old_left)| (old_left, old_right));
+            if let Some((old_left, _)) = &by_right {
+                self.take_left(&mut lefts, self.left_hasher.hash_one(old_left), old_left);
+            }
+
+            let is_pair = matches!(&by_left, Some((_, old_right)) if old_right == &right);
+            lefts.table(left_shard).insert_unique(
+                left_hash,
+                (left.clone(), right.clone()),
+                |(l, _)| self.left_hasher.hash_one(l),
+            );
+            rights
+                .table(right_shard)
+                .insert_unique(right_hash, (right, left), |(r, _)| {
+                    self.right_hasher.hash_one(r)
+                });
+
+            return match (by_left, by_right) {
+                (None, None) => Overwritten::Neither,
+                (Some(pair), None) if is_pair => Overwritten::Pair(pair.0, pair.1),
+                (Some(pair), None) => Overwritten::Left(pair.0, pair.1),
+                (None, Some(pair)) => Overwritten::Right(pair.0, pair.1),
+                (Some(by_left), Some(by_right)) => Overwritten::Both(by_left, by_right),
+            };
+        }
+    }
+
+    /// Inserts the pair only if neither value is bound, otherwise hands it back.
+    #[inline(always)]
+    pub fn try_insert(&self, left: L, right: R) -> Result<(), (L, R)> {
+        let left_hash = self.left_hasher.hash_one(&left);
+        let right_hash = self.right_hasher.hash_one(&right);
+        let (left_shard, right_shard) = (self.shard(left_hash), self.shard(right_hash));
+        let (mut lefts, mut rights) = self.lock(&[Some(left_shard)], &[Some(right_shard)]);
+        let lefts = lefts.table(left_shard);
+        let rights = rights.table(right_shard);
+        if lefts.find(left_hash, |(l, _)| *l == left).is_some()
+            || rights.find(right_hash, |(r, _)| *r == right).is_some()
+        {
+            return Err((left, right));
+        }
+
+        lefts.insert_unique(left_hash, (left.clone(), right.clone()), |(l, _)| {
+            self.left_hasher.hash_one(l)
+        });
+        rights.insert_unique(right_hash, (right, left), |(r, _)| {
+            self.right_hasher.hash_one(r)
+        });
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn get_left_cloned<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<R>
+    where
+        L: Borrow<Q>,
+    {
+        let hash = self.left_hasher.hash_one(left);
+        let shard = self.left_shards[self.shard(hash)].read().expect(POISONED);
+        let (_, right) = shard.find(hash, |(l, _)| l.borrow() == left)?;
+        Some(right.clone())
+    }
+
+    #[inline(always)]
+    pub fn get_right_cloned<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<L>
+    where
+        R: Borrow<Q>,
+    {
+        let hash = self.right_hasher.hash_one(right);
+        let shard = self.right_shards[self.shard(hash)].read().expect(POISONED);
+        let (_, left) = shard.find(hash, |(r, _)| r.borrow() == right)?;
+        Some(left.clone())
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        let hash = self.left_hasher.hash_one(left);
+        let shard = self.left_shards[self.shard(hash)].read().expect(POISONED);
+        shard.find(hash, |(l, _)| l.borrow() == left).is_some()
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        let hash = self.right_hasher.hash_one(right);
+        let shard = self.right_shards[self.shard(hash)].read().expect(POISONED);
+        shard.find(hash, |(r, _)| r.borrow() == right).is_some()
+    }
+
+    #[inline(always)]
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let hash = self.left_hasher.hash_one(left);
+        let left_shard = self.shard(hash);
+        let mut right_shard = None;
+        loop {
+            let (mut lefts, mut rights) = self.lock(&[Some(left_shard)], &[right_shard]);
+            let (_, right) = lefts
+                .table(left_shard)
+                .find(hash, |(l, _)| l.borrow() == left)?;
+            let right_hash = self.right_hasher.hash_one(right);
+            right_shard = Some(self.shard(right_hash));
+            if !rights.covers(self.shard(right_hash)) {
+                continue;
+            }
+
+            let (left, right) = self.take_left(&mut lefts, hash, left).unwrap();
+            self.take_right(&mut rights, right_hash, &right);
+            return Some((left, right));
+        }
+    }
+
+    #[inline(always)]
+    pub fn remove_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let hash = self.right_hasher.hash_one(right);
+        let right_shard = self.shard(hash);
+        let mut left_shard = None;
+        loop {
+            let (mut lefts, mut rights) = self.lock(&[left_shard], &[Some(right_shard)]);
+            let (_, left) = rights
+                .table(right_shard)
+                .find(hash, |(r, _)| r.borrow() == right)?;
+            let left_hash = self.left_hasher.hash_one(left);
+            left_shard = Some(self.shard(left_hash));
+            if !lefts.covers(self.shard(left_hash)) {
+                continue;
+            }
+
+            let (right, left) = self.take_right(&mut rights, hash, right).unwrap();
+            self.take_left(&mut lefts, left_hash, &left);
+            return Some((left, right));
+        }
+    }
+
+    /// Counts the pairs. Concurrent writers may change the count while the
+    /// shards are being visited.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.left_shards
+            .iter()
+            .map(|shard| shard.read().expect(POISONED).len())
+            .sum()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    pub fn clear(&self) {
+        let all: Vec<Option<usize>> = (0..self.left_shards.len()).map(Some).collect();
+        let (mut lefts, mut rights) = self.lock(&all, &all);
+        lefts.guards.iter_mut().for_each(|table| table.clear());
+        rights.guards.iter_mut().for_each(|table| table.clear());
+    }
+
+    #[inline(always)]
+    fn take_left<Q: ?Sized + Eq>(
+        &self,
+        lefts: &mut Locked<'_, L, R>,
+        hash: u64,
+        left: &Q,
+    ) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let entry = lefts
+            .table(self.shard(hash))
+            .find_entry(hash, |(l, _)| l.borrow() == left)
+            .ok()?;
+        Some(entry.remove().0)
+    }
+
+    #[inline(always)]
+    fn take_right<Q: ?Sized + Eq>(
+        &self,
+        rights: &mut Locked<'_, R, L>,
+        hash: u64,
+        right: &Q,
+    ) -> Option<(R, L)>
+    where
+        R: Borrow<Q>,
+    {
+        let entry = rights
+            .table(self.shard(hash))
+            .find_entry(hash, |(r, _)| r.borrow() == right)
+            .ok()?;
+        Some(entry.remove().0)
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for ConcurrentBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        let shards = thread::available_parallelism().map_or(1, usize::from) * 4;
+        Self::with_shard_amount_and_hashers(shards, LS::default(), RS::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    /// Hammers a small key space from several threads, so most writes evict
+    /// pairs whose other half lives in a different shard.
+    #[test]
+    fn threaded_updates_keep_both_sides_in_agreement() {
+        const KEYS: u64 = 64;
+        let map = ConcurrentBiMap::<u64, u64>::with_shard_amount(8);
+        thread::scope(|scope| {
+            for seed in 1..=8u64 {
+                let map = &map;
+                scope.spawn(move || {
+                    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                    for _ in 0..20_000 {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        let (left, right) = (state % KEYS, (state >> 32) % KEYS);
+                        match state >> 60 {
+                            0..=9 => drop(map.insert(left, right)),
+                            10..=11 => drop(map.try_insert(left, right)),
+                            12..=13 => drop(map.remove_left(&left)),
+                            _ => drop(map.remove_right(&right)),
+                        }
+                    }
+                });
+            }
+        });
+
+        let by_left: HashMap<u64, u64> = (0..KEYS)
+            .filter_map(|left| Some((left, map.get_left_cloned(&left)?)))
+            .collect();
+        let by_right: HashMap<u64, u64> = (0..KEYS)
+            .filter_map(|right| Some((map.get_right_cloned(&right)?, right)))
+            .collect();
+        assert_eq!(by_left, by_right);
+        assert_eq!(map.len(), by_left.len());
+    }
+
+    static PANIC_ON_EQ: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Clone, Copy)]
+    struct Key(u8);
+
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            assert!(!PANIC_ON_EQ.load(Ordering::Relaxed), "boom");
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Key {}
+
+    #[test]
+    fn panicking_eq_poisons_the_map() {
+        let map = ConcurrentBiMap::<Key, u8>::with_shard_amount(1);
+        map.insert(Key(1), 1);
+
+        PANIC_ON_EQ.store(true, Ordering::Relaxed);
+        let update = panic::catch_unwind(AssertUnwindSafe(|| map.insert(Key(1), 2)));
+        PANIC_ON_EQ.store(false, Ordering::Relaxed);
+        assert!(update.is_err());
+
+        let lookup = panic::catch_unwind(AssertUnwindSafe(|| map.get_right_cloned(&1)));
+        assert!(lookup.is_err());
+    }
+}
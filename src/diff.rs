@@ -0,0 +1,173 @@
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+use crate::BiMap;
+
+/// The changes that turn one [`BiMap`] snapshot into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiMapDiff<L, R> {
+    /// New pairs whose left value is not in the old map and that are not in
+    /// `rebound_left`. Their right value may have been bound in the old map,
+    /// to a left value the new map still has.
+    pub added: Vec<(L, R)>,
+    /// Old pairs whose left value is not in the new map and that are not in
+    /// `rebound_left`. Their right value may still be bound in the new map,
+    /// to a left value the old map already had.
+    pub removed: Vec<(L, R)>,
+    /// Left values in both maps but bound to a new right:
+    /// `(left, old_right, new_right)`.
+    pub rebound_right: Vec<(L, R, R)>,
+    /// Right values whose old left value is not in the new map and whose new
+    /// left value was not in the old map: `(old_left, new_left, right)`.
+    pub rebound_left: Vec<(L, L, R)>,
+}
+
+/// The error returned by [`BiMap::apply`] when the map does not match the
+/// base the diff was taken from. The map is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError<L, R> {
+    /// A pair the diff removes or rebinds is not in the map.
+    Missing(L, R),
+    /// A pair the diff inserts collides with a pair already in the map.
+    Conflict(L, R),
+}
+
+impl<L, R> fmt::Display for PatchError<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Missing(..) => f.write_str("pair removed by the diff is not in the map"),
+            PatchError::Conflict(..) => {
+                f.write_str("pair inserted by the diff collides with an existing pair")
+            }
+        }
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> Error for PatchError<L, R> {}
+
+impl<L, R> BiMapDiff<L, R> {
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.rebound_right.is_empty()
+            && self.rebound_left.is_empty()
+    }
+}
+
+impl<L, R, LS, RS> BiMap<L, R, LS, RS>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    /// Computes the changes from `old` to `new`. A new pair that keeps the left
+    /// value of an old pair is reported as `rebound_right`; otherwise one that
+    /// keeps the right value of an old pair whose left value is gone is
+    /// reported as `rebound_left`.
+    #[inline(always)]
+    pub fn diff(old: &Self, new: &Self) -> BiMapDiff<L, R> {
+        let mut diff = BiMapDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            rebound_right: Vec::new(),
+            rebound_left: Vec::new(),
+        };
+        for (left, right) in new.iter() {
+            if old.get_left(left) == Some(right) {
+                continue;
+            }
+            if let Some(old_right) = old.get_left(left) {
+                diff.rebound_right
+                    .push((left.clone(), old_right.clone(), right.clone()));
+            } else if let Some(old_left) = old
+                .get_right(right)
+                .filter(|old_left| !new.contains_left(*old_left))
+            {
+                diff.rebound_left
+                    .push((old_left.clone(), left.clone(), right.clone()));
+            } else {
+                diff.added.push((left.clone(), right.clone()));
+            }
+        }
+        for (left, right) in old.iter() {
+            if new.get_left(left) == Some(right) || new.contains_left(left) {
+                continue;
+            }
+            // Skip pairs already reported as `rebound_left` above.
+            if new
+                .get_right(right)
+                .is_some_and(|new_left| !old.contains_left(new_left))
+            {
+                continue;
+            }
+            diff.removed.push((left.clone(), right.clone()));
+        }
+        diff
+    }
+
+    /// Applies `diff` to this map. Either every change is applied, or none is
+    /// and the first mismatch with the diff's base is returned.
+    #[inline(always)]
+    pub fn apply(&mut self, diff: &BiMapDiff<L, R>) -> Result<(), PatchError<L, R>> {
+        let removals = diff
+            .removed
+            .iter()
+            .map(|(left, right)| (left, right))
+            .chain(
+                diff.rebound_right
+                    .iter()
+                    .map(|(left, right, _)| (left, right)),
+            )
+            .chain(
+                diff.rebound_left
+                    .iter()
+                    .map(|(left, _, right)| (left, right)),
+            );
+        let insertions = diff
+            .added
+            .iter()
+            .map(|(left, right)| (left, right))
+            .chain(
+                diff.rebound_right
+                    .iter()
+                    .map(|(left, _, right)| (left, right)),
+            )
+            .chain(
+                diff.rebound_left
+                    .iter()
+                    .map(|(_, left, right)| (left, right)),
+            );
+
+        let mut removed = Vec::new();
+        for (left, right) in removals {
+            if self.get_left(left) != Some(right) {
+                self.undo_patch(removed, &[]);
+                return Err(PatchError::Missing(left.clone(), right.clone()));
+            }
+            removed.push(self.remove_left(left).unwrap());
+        }
+        let mut inserted = Vec::new();
+        for (left, right) in insertions {
+            if self.try_insert(left.clone(), right.clone()).is_err() {
+                self.undo_patch(removed, &inserted);
+                return Err(PatchError::Conflict(left.clone(), right.clone()));
+            }
+            inserted.push(left);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn undo_patch(&mut self, removed: Vec<(L, R)>, inserted: &[&L]) {
+        for left in inserted {
+            self.remove_left(*left);
+        }
+        for (left, right) in removed {
+            self.push_new(left, right);
+        }
+    }
+}
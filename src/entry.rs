@@ -0,0 +1,336 @@
+use core::hash::{BuildHasher, Hash};
+
+use crate::{BiMap, DefaultHashBuilder};
+
+pub enum LeftEntry<'a, L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    Occupied(OccupiedLeftEntry<'a, L, R, LS, RS>),
+    Vacant(VacantLeftEntry<'a, L, R, LS, RS>),
+}
+
+pub enum RightEntry<'a, L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    Occupied(OccupiedRightEntry<'a, L, R, LS, RS>),
+    Vacant(VacantRightEntry<'a, L, R, LS, RS>),
+}
+
+pub struct OccupiedLeftEntry<'a, L, R, LS, RS> {
+    map: &'a mut BiMap<L, R, LS, RS>,
+    slot: usize,
+}
+
+pub struct VacantLeftEntry<'a, L, R, LS, RS> {
+    map: &'a mut BiMap<L, R, LS, RS>,
+    hash: u64,
+    left: L,
+}
+
+pub struct OccupiedRightEntry<'a, L, R, LS, RS> {
+    map: &'a mut BiMap<L, R, LS, RS>,
+    slot: usize,
+}
+
+pub struct VacantRightEntry<'a, L, R, LS, RS> {
+    map: &'a mut BiMap<L, R, LS, RS>,
+    hash: u64,
+    right: R,
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> LeftEntry<'a, L, R, LS, RS> {
+    #[inline(always)]
+    pub(crate) fn new(map: &'a mut BiMap<L, R, LS, RS>, left: L) -> Self {
+        let hash = map.left.hash(&left);
+        match map.left.find(hash, &left) {
+            Some(slot) => LeftEntry::Occupied(OccupiedLeftEntry { map, slot }),
+            None => LeftEntry::Vacant(VacantLeftEntry { map, hash, left }),
+        }
+    }
+
+    #[inline(always)]
+    pub fn key(&self) -> &L {
+        match self {
+            LeftEntry::Occupied(entry) => entry.key(),
+            LeftEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the right value, inserting `right` if the entry is vacant. An
+    /// insertion also returns the pair that previously held `right`, if any.
+    #[inline(always)]
+    pub fn or_insert(self, right: R) -> (&'a R, Option<(L, R)>) {
+        match self {
+            LeftEntry::Occupied(entry) => (entry.into_ref(), None),
+            LeftEntry::Vacant(entry) => entry.insert(right),
+        }
+    }
+
+    /// Like [`or_insert`](Self::or_insert), computing the right value lazily.
+    #[inline(always)]
+    pub fn or_insert_with<F: FnOnce() -> R>(self, f: F) -> (&'a R, Option<(L, R)>) {
+        match self {
+            LeftEntry::Occupied(entry) => (entry.into_ref(), None),
+            LeftEntry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Like [`or_insert`](Self::or_insert), computing the right value from the key.
+    #[inline(always)]
+    pub fn or_insert_with_key<F: FnOnce(&L) -> R>(self, f: F) -> (&'a R, Option<(L, R)>) {
+        match self {
+            LeftEntry::Occupied(entry) => (entry.into_ref(), None),
+            LeftEntry::Vacant(entry) => {
+                let right = f(entry.key());
+                entry.insert(right)
+            }
+        }
+    }
+
+    /// Replaces the right value of an occupied entry with `f(&old_right)`,
+    /// returning the other pair that already held the new right value, if any.
+    #[inline(always)]
+    pub fn and_replace<F: FnOnce(&R) -> R>(self, f: F) -> (Self, Option<(L, R)>) {
+        match self {
+            LeftEntry::Occupied(mut entry) => {
+                let right = f(entry.get());
+                let (_, evicted) = entry.insert(right);
+                (LeftEntry::Occupied(entry), evicted)
+            }
+            LeftEntry::Vacant(entry) => (LeftEntry::Vacant(entry), None),
+        }
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>
+    OccupiedLeftEntry<'a, L, R, LS, RS>
+{
+    #[inline(always)]
+    pub fn key(&self) -> &L {
+        self.map.left.get(self.slot)
+    }
+
+    #[inline(always)]
+    pub fn get(&self) -> &R {
+        self.map.right.get(self.slot)
+    }
+
+    #[inline(always)]
+    pub fn into_ref(self) -> &'a R {
+        self.map.right.get(self.slot)
+    }
+
+    /// Binds the entry's left value to `right`, returning the old right value
+    /// and the pair that previously held `right`, if any.
+    #[inline(always)]
+    pub fn insert(&mut self, right: R) -> (R, Option<(L, R)>) {
+        let hash = self.map.right.hash(&right);
+        match self.map.right.find(hash, &right) {
+            Some(slot) if slot == self.slot => (self.map.right.replace_equal(slot, right), None),
+            Some(slot) => {
+                let evicted = self.map.swap_remove(slot);
+                if self.slot == self.map.len() {
+                    self.slot = slot;
+                }
+                (
+                    self.map.right.replace(self.slot, hash, right),
+                    Some(evicted),
+                )
+            }
+            None => (self.map.right.replace(self.slot, hash, right), None),
+        }
+    }
+
+    #[inline(always)]
+    pub fn remove(self) -> (L, R) {
+        self.map.swap_remove(self.slot)
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>
+    VacantLeftEntry<'a, L, R, LS, RS>
+{
+    #[inline(always)]
+    pub fn key(&self) -> &L {
+        &self.left
+    }
+
+    #[inline(always)]
+    pub fn into_key(self) -> L {
+        self.left
+    }
+
+    /// Binds the entry's left value to `right`, returning the pair that
+    /// previously held `right`, if any.
+    #[inline(always)]
+    pub fn insert(self, right: R) -> (&'a R, Option<(L, R)>) {
+        let (entry, evicted) = self.insert_entry(right);
+        (entry.into_ref(), evicted)
+    }
+
+    #[allow(clippy::type_complexity)]
+    #[inline(always)]
+    pub fn insert_entry(self, right: R) -> (OccupiedLeftEntry<'a, L, R, LS, RS>, Option<(L, R)>) {
+        let hash = self.map.right.hash(&right);
+        let evicted = self
+            .map
+            .right
+            .find(hash, &right)
+            .map(|slot| self.map.swap_remove(slot));
+        let slot = self.map.len();
+        self.map.push(self.hash, self.left, hash, right);
+        (
+            OccupiedLeftEntry {
+                map: self.map,
+                slot,
+            },
+            evicted,
+        )
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>
+    RightEntry<'a, L, R, LS, RS>
+{
+    #[inline(always)]
+    pub(crate) fn new(map: &'a mut BiMap<L, R, LS, RS>, right: R) -> Self {
+        let hash = map.right.hash(&right);
+        match map.right.find(hash, &right) {
+            Some(slot) => RightEntry::Occupied(OccupiedRightEntry { map, slot }),
+            None => RightEntry::Vacant(VacantRightEntry { map, hash, right }),
+        }
+    }
+
+    #[inline(always)]
+    pub fn key(&self) -> &R {
+        match self {
+            RightEntry::Occupied(entry) => entry.key(),
+            RightEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Returns the left value, inserting `left` if the entry is vacant. An
+    /// insertion also returns the pair that previously held `left`, if any.
+    #[inline(always)]
+    pub fn or_insert(self, left: L) -> (&'a L, Option<(L, R)>) {
+        match self {
+            RightEntry::Occupied(entry) => (entry.into_ref(), None),
+            RightEntry::Vacant(entry) => entry.insert(left),
+        }
+    }
+
+    /// Like [`or_insert`](Self::or_insert), computing the left value lazily.
+    #[inline(always)]
+    pub fn or_insert_with<F: FnOnce() -> L>(self, f: F) -> (&'a L, Option<(L, R)>) {
+        match self {
+            RightEntry::Occupied(entry) => (entry.into_ref(), None),
+            RightEntry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Like [`or_insert`](Self::or_insert), computing the left value from the key.
+    #[inline(always)]
+    pub fn or_insert_with_key<F: FnOnce(&R) -> L>(self, f: F) -> (&'a L, Option<(L, R)>) {
+        match self {
+            RightEntry::Occupied(entry) => (entry.into_ref(), None),
+            RightEntry::Vacant(entry) => {
+                let left = f(entry.key());
+                entry.insert(left)
+            }
+        }
+    }
+
+    /// Replaces the left value of an occupied entry with `f(&old_left)`,
+    /// returning the other pair that already held the new left value, if any.
+    #[inline(always)]
+    pub fn and_replace<F: FnOnce(&L) -> L>(self, f: F) -> (Self, Option<(L, R)>) {
+        match self {
+            RightEntry::Occupied(mut entry) => {
+                let left = f(entry.get());
+                let (_, evicted) = entry.insert(left);
+                (RightEntry::Occupied(entry), evicted)
+            }
+            RightEntry::Vacant(entry) => (RightEntry::Vacant(entry), None),
+        }
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>
+    OccupiedRightEntry<'a, L, R, LS, RS>
+{
+    #[inline(always)]
+    pub fn key(&self) -> &R {
+        self.map.right.get(self.slot)
+    }
+
+    #[inline(always)]
+    pub fn get(&self) -> &L {
+        self.map.left.get(self.slot)
+    }
+
+    #[inline(always)]
+    pub fn into_ref(self) -> &'a L {
+        self.map.left.get(self.slot)
+    }
+
+    /// Binds the entry's right value to `left`, returning the old left value
+    /// and the pair that previously held `left`, if any.
+    #[inline(always)]
+    pub fn insert(&mut self, left: L) -> (L, Option<(L, R)>) {
+        let hash = self.map.left.hash(&left);
+        match self.map.left.find(hash, &left) {
+            Some(slot) if slot == self.slot => (self.map.left.replace_equal(slot, left), None),
+            Some(slot) => {
+                let evicted = self.map.swap_remove(slot);
+                if self.slot == self.map.len() {
+                    self.slot = slot;
+                }
+                (self.map.left.replace(self.slot, hash, left), Some(evicted))
+            }
+            None => (self.map.left.replace(self.slot, hash, left), None),
+        }
+    }
+
+    #[inline(always)]
+    pub fn remove(self) -> (L, R) {
+        self.map.swap_remove(self.slot)
+    }
+}
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>
+    VacantRightEntry<'a, L, R, LS, RS>
+{
+    #[inline(always)]
+    pub fn key(&self) -> &R {
+        &self.right
+    }
+
+    #[inline(always)]
+    pub fn into_key(self) -> R {
+        self.right
+    }
+
+    /// Binds the entry's right value to `left`, returning the pair that
+    /// previously held `left`, if any.
+    #[inline(always)]
+    pub fn insert(self, left: L) -> (&'a L, Option<(L, R)>) {
+        let (entry, evicted) = self.insert_entry(left);
+        (entry.into_ref(), evicted)
+    }
+
+    #[allow(clippy::type_complexity)]
+    #[inline(always)]
+    pub fn insert_entry(self, left: L) -> (OccupiedRightEntry<'a, L, R, LS, RS>, Option<(L, R)>) {
+        let hash = self.map.left.hash(&left);
+        let evicted = self
+            .map
+            .left
+            .find(hash, &left)
+            .map(|slot| self.map.swap_remove(slot));
+        let slot = self.map.len();
+        self.map.push(hash, left, self.hash, self.right);
+        (
+            OccupiedRightEntry {
+                map: self.map,
+                slot,
+            },
+            evicted,
+        )
+    }
+}
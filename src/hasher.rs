@@ -0,0 +1,77 @@
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+
+#[cfg(feature = "std")]
+type Inner = std::hash::RandomState;
+#[cfg(not(feature = "std"))]
+type Inner = hashbrown::DefaultHashBuilder;
+
+/// The hasher used when none is given. It is the same type with and without
+/// the `std` feature, so enabling `std` never changes a map's type; only the
+/// algorithm inside differs. With `std` it wraps std's randomly seeded
+/// `RandomState`, and without it hashbrown's default hasher.
+#[derive(Clone, Default)]
+pub struct DefaultHashBuilder(Inner);
+
+impl BuildHasher for DefaultHashBuilder {
+    type Hasher = DefaultHasher;
+
+    #[inline(always)]
+    fn build_hasher(&self) -> DefaultHasher {
+        DefaultHasher(self.0.build_hasher())
+    }
+}
+
+impl fmt::Debug for DefaultHashBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultHashBuilder").finish_non_exhaustive()
+    }
+}
+
+/// The hasher built by [`DefaultHashBuilder`].
+#[derive(Clone)]
+pub struct DefaultHasher(<Inner as BuildHasher>::Hasher);
+
+macro_rules! forward_writes {
+    ($($method:ident($ty:ty)),*) => {
+        $(
+            #[inline(always)]
+            fn $method(&mut self, i: $ty) {
+                self.0.$method(i);
+            }
+        )*
+    };
+}
+
+impl Hasher for DefaultHasher {
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    #[inline(always)]
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    forward_writes!(
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_u128(u128),
+        write_usize(usize),
+        write_i8(i8),
+        write_i16(i16),
+        write_i32(i32),
+        write_i64(i64),
+        write_i128(i128),
+        write_isize(isize)
+    );
+}
+
+impl fmt::Debug for DefaultHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultHasher").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,269 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{BiMap, DefaultHashBuilder, InsertConflict, Overwritten};
+
+/// A bimap that keeps its pairs in insertion order and gives each pair a
+/// position that can be used for lookups.
+pub struct IndexBiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    map: BiMap<L, R, LS, RS>,
+}
+
+impl<L, R> IndexBiMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self { map: BiMap::new() }
+    }
+
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: BiMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<L, R, LS, RS> IndexBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn with_hashers(left_hasher: LS, right_hasher: RS) -> Self {
+        Self {
+            map: BiMap::with_hashers(left_hasher, right_hasher),
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_capacity_and_hashers(capacity: usize, left_hasher: LS, right_hasher: RS) -> Self {
+        Self {
+            map: BiMap::with_capacity_and_hashers(capacity, left_hasher, right_hasher),
+        }
+    }
+
+    #[inline(always)]
+    pub fn left_hasher(&self) -> &LS {
+        self.map.left_hasher()
+    }
+
+    #[inline(always)]
+    pub fn right_hasher(&self) -> &RS {
+        self.map.right_hasher()
+    }
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> IndexBiMap<L, R, LS, RS> {
+    /// Inserts a pair. A new pair is appended; a pair that replaces existing
+    /// ones takes the position of the pair that held `left`, or of the pair
+    /// that held `right` if `left` was unbound.
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let map = &mut self.map;
+        let left_hash = map.left.hash(&left);
+        let right_hash = map.right.hash(&right);
+        match (
+            map.left.find(left_hash, &left),
+            map.right.find(right_hash, &right),
+        ) {
+            (Some(i), Some(j)) if i != j => {
+                let by_right = map.shift_remove(j);
+                let i = if j < i { i - 1 } else { i };
+                let by_left = (
+                    map.left.replace_equal(i, left),
+                    map.right.replace(i, right_hash, right),
+                );
+                Overwritten::Both(by_left, by_right)
+            }
+            _ => map.insert(left, right),
+        }
+    }
+
+    #[inline(always)]
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        self.map.try_insert(left, right)
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        self.map.get_left(left)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+    {
+        self.map.get_right(right)
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.map.contains_left(left)
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.map.contains_right(right)
+    }
+
+    #[inline(always)]
+    pub fn index_of_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<usize>
+    where
+        L: Borrow<Q>,
+    {
+        self.map.find_left(left)
+    }
+
+    #[inline(always)]
+    pub fn index_of_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<usize>
+    where
+        R: Borrow<Q>,
+    {
+        self.map.find_right(right)
+    }
+
+    /// Removes the pair holding `left`, moving the last pair into its position.
+    #[inline(always)]
+    pub fn swap_remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        self.map.remove_left(left)
+    }
+
+    /// Removes the pair holding `right`, moving the last pair into its position.
+    #[inline(always)]
+    pub fn swap_remove_right<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        self.map.remove_right(right)
+    }
+
+    /// Removes the pair holding `left`, shifting every later pair down by one.
+    #[inline(always)]
+    pub fn shift_remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let index = self.map.find_left(left)?;
+        Some(self.map.shift_remove(index))
+    }
+
+    /// Removes the pair holding `right`, shifting every later pair down by one.
+    #[inline(always)]
+    pub fn shift_remove_right<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let index = self.map.find_right(right)?;
+        Some(self.map.shift_remove(index))
+    }
+
+    #[inline(always)]
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(L, R)> {
+        (index < self.len()).then(|| self.map.swap_remove(index))
+    }
+
+    #[inline(always)]
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(L, R)> {
+        (index < self.len()).then(|| self.map.shift_remove(index))
+    }
+
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<(L, R)> {
+        let index = self.len().checked_sub(1)?;
+        Some(self.map.swap_remove(index))
+    }
+
+    /// Sorts the pairs in place with `compare`, which receives the left and
+    /// right values of two pairs.
+    #[inline(always)]
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&L, &R, &L, &R) -> Ordering,
+    {
+        let lefts = self.map.left.take_values();
+        let rights = self.map.right.take_values();
+        let mut pairs: Vec<(L, R)> = lefts.into_iter().zip(rights).collect();
+        pairs.sort_by(|a, b| compare(&a.0, &a.1, &b.0, &b.1));
+        let (lefts, rights) = pairs.into_iter().unzip();
+        self.map.left.set_values(lefts);
+        self.map.right.set_values(rights);
+    }
+}
+
+impl<L, R, LS, RS> IndexBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn get_index(&self, index: usize) -> Option<(&L, &R)> {
+        (index < self.len()).then(|| self.map.pair(index))
+    }
+
+    #[inline(always)]
+    pub fn first(&self) -> Option<(&L, &R)> {
+        self.get_index(0)
+    }
+
+    #[inline(always)]
+    pub fn last(&self) -> Option<(&L, &R)> {
+        self.get_index(self.len().checked_sub(1)?)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.map.left.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.map.left.clear();
+        self.map.right.clear();
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl DoubleEndedIterator<Item = &L> + ExactSizeIterator {
+        self.map.left.values().iter()
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl DoubleEndedIterator<Item = &R> + ExactSizeIterator {
+        self.map.right.values().iter()
+    }
+
+    /// Iterates over the pairs in order.
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&L, &R)> + ExactSizeIterator {
+        self.map.left.values().iter().zip(self.map.right.values())
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for IndexBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            map: BiMap::default(),
+        }
+    }
+}
+
+impl<L: Clone, R: Clone, LS: Clone, RS: Clone> Clone for IndexBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use core::hash::{BuildHasher, Hash};
+
+use crate::DefaultHashBuilder;
+use crate::raw::Side;
+
+/// A compact id handed out by an [`Interner`]. Ids are dense and assigned in
+/// interning order, starting from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    #[inline(always)]
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Assigns each distinct value a [`Symbol`] and stores it exactly once. This is
+/// one side of a bimap whose other side is the slot number itself, so
+/// resolving a symbol is an array index rather than a hash lookup.
+pub struct Interner<T: ?Sized = str, S = DefaultHashBuilder> {
+    values: Side<Box<T>, S>,
+}
+
+impl<T: ?Sized> Interner<T> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<T: ?Sized, S> Interner<T, S> {
+    #[inline(always)]
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            values: Side::with_hasher(hasher),
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            values: Side::with_capacity_and_hasher(capacity, hasher),
+        }
+    }
+
+    #[inline(always)]
+    pub fn hasher(&self) -> &S {
+        self.values.hasher()
+    }
+
+    /// Returns the value interned as `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` was not handed out by this interner.
+    #[inline(always)]
+    pub fn resolve(&self, symbol: Symbol) -> &T {
+        self.values.get(symbol.0 as usize)
+    }
+
+    #[inline(always)]
+    pub fn try_resolve(&self, symbol: Symbol) -> Option<&T> {
+        self.values
+            .values()
+            .get(symbol.0 as usize)
+            .map(|value| &**value)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.values.len() == 0
+    }
+
+    /// Iterates over the interned values in symbol order.
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Symbol, &T)> + ExactSizeIterator {
+        self.values
+            .values()
+            .iter()
+            .enumerate()
+            .map(|(slot, value)| (Symbol(slot as u32), &**value))
+    }
+}
+
+impl<T: ?Sized + Eq + Hash, S: BuildHasher> Interner<T, S> {
+    /// Returns the symbol for `value`, interning a copy of it if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` values have been interned.
+    #[inline(always)]
+    pub fn intern(&mut self, value: &T) -> Symbol
+    where
+        T: ToOwned,
+        T::Owned: Into<Box<T>>,
+    {
+        let hash = self.values.hash(value);
+        if let Some(slot) = self.values.find(hash, value) {
+            return Symbol(slot as u32);
+        }
+
+        let symbol = u32::try_from(self.values.len()).expect("interner is full");
+        self.values.push(hash, value.to_owned().into());
+        Symbol(symbol)
+    }
+
+    /// Returns the symbol for `value` if it has been interned.
+    #[inline(always)]
+    pub fn get(&self, value: &T) -> Option<Symbol> {
+        let slot = self.values.find(self.values.hash(value), value)?;
+        Some(Symbol(slot as u32))
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, value: &T) -> bool {
+        self.get(value).is_some()
+    }
+}
+
+impl<T: ?Sized, S: Default> Default for Interner<T, S> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: ?Sized, S: Clone> Clone for Interner<T, S>
+where
+    Box<T>: Clone,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_unsized_values() {
+        let mut interner = Interner::new();
+        let a = interner.intern("a");
+        let mut clone = interner.clone();
+        let b = clone.intern("b");
+        assert_eq!(clone.resolve(a), "a");
+        assert_eq!(clone.resolve(b), "b");
+        assert_eq!(clone.get("a"), Some(a));
+        assert!(!interner.contains("b"));
+    }
+}
@@ -0,0 +1,64 @@
+use alloc::vec;
+use core::iter::{FusedIterator, Zip};
+use core::slice;
+
+/// A borrowing iterator over the pairs of a [`BiMap`](crate::BiMap).
+#[derive(Clone)]
+pub struct Iter<'a, L, R> {
+    pub(crate) inner: Zip<slice::Iter<'a, L>, slice::Iter<'a, R>>,
+}
+
+/// An owning iterator over the pairs of a [`BiMap`](crate::BiMap).
+pub struct IntoIter<L, R> {
+    pub(crate) inner: Zip<vec::IntoIter<L>, vec::IntoIter<R>>,
+}
+
+impl<'a, L, R> Iterator for Iter<'a, L, R> {
+    type Item = (&'a L, &'a R);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<L, R> DoubleEndedIterator for Iter<'_, L, R> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<L, R> ExactSizeIterator for Iter<'_, L, R> {}
+
+impl<L, R> FusedIterator for Iter<'_, L, R> {}
+
+impl<L, R> Iterator for IntoIter<L, R> {
+    type Item = (L, R);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<L, R> DoubleEndedIterator for IntoIter<L, R> {
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<L, R> ExactSizeIterator for IntoIter<L, R> {}
+
+impl<L, R> FusedIterator for IntoIter<L, R> {}
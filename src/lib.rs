@@ -1,136 +1,551 @@
-use std::collections::HashMap;
-use std::hash::Hash;
+#![no_std]
 
-pub struct BiMap<L, R> {
-    left_to_right: HashMap<L, R>,
-    right_to_left: HashMap<R, L>,
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+mod array;
+#[cfg(target_has_atomic = "ptr")]
+mod btree;
+mod compose;
+#[cfg(feature = "std")]
+mod concurrent;
+mod diff;
+mod entry;
+mod hasher;
+mod index;
+mod interner;
+mod iter;
+mod multi;
+mod observe;
+#[cfg(target_has_atomic = "ptr")]
+mod persistent;
+mod raw;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod set_ops;
+mod static_map;
+mod surjective;
+mod transaction;
+mod vec_map;
+
+pub use array::{ArrayBiMap, CapacityError, TryInsertError};
+#[cfg(target_has_atomic = "ptr")]
+pub use btree::BTreeBiMap;
+pub use compose::{Composed, Inverse};
+#[cfg(feature = "std")]
+pub use concurrent::ConcurrentBiMap;
+pub use diff::{BiMapDiff, PatchError};
+pub use entry::{
+    LeftEntry, OccupiedLeftEntry, OccupiedRightEntry, RightEntry, VacantLeftEntry, VacantRightEntry,
+};
+pub use hasher::{DefaultHashBuilder, DefaultHasher};
+pub use index::IndexBiMap;
+pub use interner::{Interner, Symbol};
+pub use iter::{IntoIter, Iter};
+pub use multi::BiMultiMap;
+pub use observe::{Event, ObservedBiMap, SubscriptionId};
+#[cfg(target_has_atomic = "ptr")]
+pub use persistent::PersistentBiMap;
+pub use set_ops::{ConflictPolicy, MergeConflict};
+#[doc(hidden)]
+pub use static_map::PerfectHash;
+pub use static_map::{StaticBiMap, StaticKey};
+pub use surjective::SurjectiveMap;
+pub use transaction::{Transaction, UndoBiMap};
+pub use vec_map::VecBiMap;
+
+use core::borrow::Borrow;
+use core::error::Error;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+use raw::Side;
+
+/// The pairs displaced by [`BiMap::insert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// Neither value was bound; nothing was displaced.
+    Neither,
+    /// The left value was bound; holds the old pair that contained it.
+    Left(L, R),
+    /// The right value was bound; holds the old pair that contained it.
+    Right(L, R),
+    /// The exact pair was already present; holds the old pair.
+    Pair(L, R),
+    /// Both values were bound in different pairs; holds the pair that contained
+    /// the left value, then the pair that contained the right value.
+    Both((L, R), (L, R)),
+}
+
+impl<L, R> Overwritten<L, R> {
+    #[inline(always)]
+    pub fn did_overwrite(&self) -> bool {
+        !matches!(self, Overwritten::Neither)
+    }
+}
+
+/// The error returned by [`BiMap::try_insert`] when either value is already bound.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InsertConflict<'a, L, R> {
+    pub left: L,
+    pub right: R,
+    /// The existing pair that contains `left`, if any.
+    pub existing_left: Option<(&'a L, &'a R)>,
+    /// The existing pair that contains `right`, if any.
+    pub existing_right: Option<(&'a L, &'a R)>,
+}
+
+impl<L, R> InsertConflict<'_, L, R> {
+    #[inline(always)]
+    pub fn into_pair(self) -> (L, R) {
+        (self.left, self.right)
+    }
+}
+
+impl<L, R> fmt::Display for InsertConflict<'_, L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.existing_left.is_some(), self.existing_right.is_some()) {
+            (true, true) => f.write_str("left and right values are already bound"),
+            (true, false) => f.write_str("left value is already bound"),
+            _ => f.write_str("right value is already bound"),
+        }
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> Error for InsertConflict<'_, L, R> {}
+
+pub struct BiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    left: Side<L, LS>,
+    right: Side<R, RS>,
 }
 
-impl<L: Eq + Hash + Clone, R: Eq + Hash + Clone> BiMap<L, R> {
+impl<L, R> BiMap<L, R> {
     #[inline(always)]
     pub fn new() -> Self {
+        Self::with_hashers(DefaultHashBuilder::default(), DefaultHashBuilder::default())
+    }
+
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hashers(
+            capacity,
+            DefaultHashBuilder::default(),
+            DefaultHashBuilder::default(),
+        )
+    }
+}
+
+impl<L, R, LS, RS> BiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn with_hashers(left_hasher: LS, right_hasher: RS) -> Self {
         Self {
-            left_to_right: HashMap::new(),
-            right_to_left: HashMap::new(),
+            left: Side::with_hasher(left_hasher),
+            right: Side::with_hasher(right_hasher),
         }
     }
 
     #[inline(always)]
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity_and_hashers(capacity: usize, left_hasher: LS, right_hasher: RS) -> Self {
         Self {
-            left_to_right: HashMap::with_capacity(capacity),
-            right_to_left: HashMap::with_capacity(capacity),
+            left: Side::with_capacity_and_hasher(capacity, left_hasher),
+            right: Side::with_capacity_and_hasher(capacity, right_hasher),
         }
     }
 
     #[inline(always)]
-    pub fn insert(&mut self, left: L, right: R) -> Option<(L, R)> {
-        if let Some(old_left) = self.right_to_left.get(&right) {
-            if old_left != &left {
-                let old_left = old_left.clone();
-                let old_right = self.left_to_right.remove(&old_left).unwrap();
-                self.right_to_left.remove(&right);
-                self.left_to_right.insert(left.clone(), right.clone());
-                self.right_to_left.insert(right, left.clone());
-                return Some((old_left, old_right));
-            }
+    pub fn left_hasher(&self) -> &LS {
+        self.left.hasher()
+    }
+
+    #[inline(always)]
+    pub fn right_hasher(&self) -> &RS {
+        self.right.hasher()
+    }
+
+    /// Swaps the left and right sides without moving any values.
+    #[inline(always)]
+    pub fn into_flipped(self) -> BiMap<R, L, RS, LS> {
+        BiMap {
+            left: self.right,
+            right: self.left,
         }
+    }
+
+    #[inline(always)]
+    fn pair(&self, slot: usize) -> (&L, &R) {
+        (self.left.get(slot), self.right.get(slot))
+    }
+}
 
-        if let Some(old_right) = self.left_to_right.get(&left) {
-            if old_right == &right {
-                return None;
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> BiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let left_hash = self.left.hash(&left);
+        let right_hash = self.right.hash(&right);
+        match (
+            self.left.find(left_hash, &left),
+            self.right.find(right_hash, &right),
+        ) {
+            (None, None) => {
+                self.push(left_hash, left, right_hash, right);
+                Overwritten::Neither
             }
-            let old_right = old_right.clone();
-            self.right_to_left.remove(&old_right);
-            self.left_to_right.insert(left.clone(), right.clone());
-            self.right_to_left.insert(right, left.clone());
-            return Some((left, old_right));
+            (Some(i), Some(j)) if i == j => Overwritten::Pair(
+                self.left.replace_equal(i, left),
+                self.right.replace_equal(i, right),
+            ),
+            (Some(i), None) => Overwritten::Left(
+                self.left.replace_equal(i, left),
+                self.right.replace(i, right_hash, right),
+            ),
+            (None, Some(j)) => Overwritten::Right(
+                self.left.replace(j, left_hash, left),
+                self.right.replace_equal(j, right),
+            ),
+            (Some(i), Some(j)) => {
+                // Remove the higher slot first so the lower one stays in place.
+                let (by_left, by_right) = if i > j {
+                    let by_left = self.swap_remove(i);
+                    (by_left, self.swap_remove(j))
+                } else {
+                    let by_right = self.swap_remove(j);
+                    (self.swap_remove(i), by_right)
+                };
+                self.push(left_hash, left, right_hash, right);
+                Overwritten::Both(by_left, by_right)
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        let left_hash = self.left.hash(&left);
+        let right_hash = self.right.hash(&right);
+        let by_left = self.left.find(left_hash, &left);
+        let by_right = self.right.find(right_hash, &right);
+        if by_left.is_some() || by_right.is_some() {
+            return Err(InsertConflict {
+                left,
+                right,
+                existing_left: by_left.map(|slot| self.pair(slot)),
+                existing_right: by_right.map(|slot| self.pair(slot)),
+            });
         }
 
-        self.left_to_right.insert(left.clone(), right.clone());
-        self.right_to_left.insert(right, left);
-        None
+        self.push(left_hash, left, right_hash, right);
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn entry_left(&mut self, left: L) -> LeftEntry<'_, L, R, LS, RS> {
+        LeftEntry::new(self, left)
+    }
+
+    #[inline(always)]
+    pub fn entry_right(&mut self, right: R) -> RightEntry<'_, L, R, LS, RS> {
+        RightEntry::new(self, right)
     }
 
     #[inline(always)]
-    pub fn get_left(&self, left: &L) -> Option<&R> {
-        self.left_to_right.get(left)
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        let slot = self.find_left(left)?;
+        Some(self.right.get(slot))
     }
 
     #[inline(always)]
-    pub fn get_right(&self, right: &R) -> Option<&L> {
-        self.right_to_left.get(right)
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+    {
+        let slot = self.find_right(right)?;
+        Some(self.left.get(slot))
     }
 
     #[inline(always)]
-    pub fn remove_left(&mut self, left: &L) -> Option<(L, R)> {
-        let right = self.left_to_right.remove(left)?;
-        let left = self.right_to_left.remove(&right)?;
-        Some((left, right))
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let slot = self.find_left(left)?;
+        Some(self.swap_remove(slot))
     }
 
     #[inline(always)]
-    pub fn remove_right(&mut self, right: &R) -> Option<(L, R)> {
-        let left = self.right_to_left.remove(right)?;
-        let right = self.left_to_right.remove(&left)?;
-        Some((left, right))
+    pub fn remove_right<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let slot = self.find_right(right)?;
+        Some(self.swap_remove(slot))
     }
 
     #[inline(always)]
-    pub fn contains_left(&self, left: &L) -> bool {
-        self.left_to_right.contains_key(left)
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.find_left(left).is_some()
     }
 
     #[inline(always)]
-    pub fn contains_right(&self, right: &R) -> bool {
-        self.right_to_left.contains_key(right)
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.find_right(right).is_some()
     }
 
     #[inline(always)]
     pub fn len(&self) -> usize {
-        self.left_to_right.len()
+        self.left.len()
     }
 
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        self.left_to_right.is_empty()
+        self.left.len() == 0
     }
 
     #[inline(always)]
     pub fn clear(&mut self) {
-        self.left_to_right.clear();
-        self.right_to_left.clear();
+        self.left.clear();
+        self.right.clear();
     }
 
     #[inline(always)]
     pub fn left_values(&self) -> impl Iterator<Item = &L> {
-        self.left_to_right.keys()
+        self.left.values().iter()
     }
 
     #[inline(always)]
     pub fn right_values(&self) -> impl Iterator<Item = &R> {
-        self.right_to_left.keys()
+        self.right.values().iter()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> Iter<'_, L, R> {
+        Iter {
+            inner: self.left.values().iter().zip(self.right.values()),
+        }
+    }
+
+    #[inline(always)]
+    pub fn retain<F: FnMut(&L, &R) -> bool>(&mut self, mut f: F) {
+        let mut slot = 0;
+        while slot < self.len() {
+            if f(self.left.get(slot), self.right.get(slot)) {
+                slot += 1;
+            } else {
+                self.swap_remove(slot);
+            }
+        }
     }
 
+    /// Removes every pair, yielding them by value. The map is empty afterwards
+    /// even if the iterator is dropped early.
     #[inline(always)]
-    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
-        self.left_to_right.iter()
+    pub fn drain(&mut self) -> impl Iterator<Item = (L, R)> {
+        self.left.drain().zip(self.right.drain())
+    }
+
+    /// Removes and yields the pairs for which `pred` returns `true`. Pairs not
+    /// yet visited when the iterator is dropped stay in the map.
+    #[inline(always)]
+    pub fn extract_if<F: FnMut(&L, &R) -> bool>(
+        &mut self,
+        mut pred: F,
+    ) -> impl Iterator<Item = (L, R)> {
+        let mut slot = 0;
+        core::iter::from_fn(move || {
+            while slot < self.len() {
+                if pred(self.left.get(slot), self.right.get(slot)) {
+                    return Some(self.swap_remove(slot));
+                }
+                slot += 1;
+            }
+            None
+        })
+    }
+
+    #[inline(always)]
+    fn find_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<usize>
+    where
+        L: Borrow<Q>,
+    {
+        self.left.find(self.left.hash(left), left)
+    }
+
+    #[inline(always)]
+    fn find_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<usize>
+    where
+        R: Borrow<Q>,
+    {
+        self.right.find(self.right.hash(right), right)
+    }
+
+    #[inline(always)]
+    fn push(&mut self, left_hash: u64, left: L, right_hash: u64, right: R) {
+        self.left.push(left_hash, left);
+        self.right.push(right_hash, right);
+    }
+
+    /// Appends a pair whose values are both known to be unbound.
+    #[inline(always)]
+    fn push_new(&mut self, left: L, right: R) {
+        let left_hash = self.left.hash(&left);
+        let right_hash = self.right.hash(&right);
+        self.push(left_hash, left, right_hash, right);
+    }
+
+    #[inline(always)]
+    fn swap_remove(&mut self, slot: usize) -> (L, R) {
+        (self.left.swap_remove(slot), self.right.swap_remove(slot))
+    }
+
+    #[inline(always)]
+    fn shift_remove(&mut self, slot: usize) -> (L, R) {
+        (self.left.shift_remove(slot), self.right.shift_remove(slot))
+    }
+
+    /// Puts a pair back into `slot`, undoing a [`BiMap::swap_remove`].
+    #[inline(always)]
+    fn restore(&mut self, slot: usize, left: L, right: R) {
+        let left_hash = self.left.hash(&left);
+        let right_hash = self.right.hash(&right);
+        self.left.restore(slot, left_hash, left);
+        self.right.restore(slot, right_hash, right);
+    }
+
+    /// Replaces the pair in `slot`, whose values may hash differently.
+    #[inline(always)]
+    fn replace(&mut self, slot: usize, left: L, right: R) -> (L, R) {
+        let left_hash = self.left.hash(&left);
+        let right_hash = self.right.hash(&right);
+        (
+            self.left.replace(slot, left_hash, left),
+            self.right.replace(slot, right_hash, right),
+        )
     }
 }
 
-impl<L: Eq + Hash + Clone, R: Eq + Hash + Clone> Default for BiMap<L, R> {
+impl<L, R, LS: Default, RS: Default> Default for BiMap<L, R, LS, RS> {
     #[inline(always)]
     fn default() -> Self {
-        Self::new()
+        Self::with_hashers(LS::default(), RS::default())
     }
 }
 
-impl<L: Eq + Hash + Clone, R: Eq + Hash + Clone> Clone for BiMap<L, R> {
+impl<L: Clone, R: Clone, LS: Clone, RS: Clone> Clone for BiMap<L, R, LS, RS> {
     #[inline(always)]
     fn clone(&self) -> Self {
         Self {
-            left_to_right: self.left_to_right.clone(),
-            right_to_left: self.right_to_left.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<L, R, LS, RS> FromIterator<(L, R)> for BiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher + Default,
+    RS: BuildHasher + Default,
+{
+    #[inline(always)]
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> Extend<(L, R)>
+    for BiMap<L, R, LS, RS>
+{
+    #[inline(always)]
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, const N: usize> From<[(L, R); N]> for BiMap<L, R> {
+    #[inline(always)]
+    fn from(pairs: [(L, R); N]) -> Self {
+        pairs.into_iter().collect()
+    }
+}
+
+impl<L, R, LS, RS> IntoIterator for BiMap<L, R, LS, RS> {
+    type Item = (L, R);
+    type IntoIter = IntoIter<L, R>;
+
+    #[inline(always)]
+    fn into_iter(self) -> IntoIter<L, R> {
+        IntoIter {
+            inner: self
+                .left
+                .into_values()
+                .into_iter()
+                .zip(self.right.into_values()),
         }
     }
 }
+
+impl<'a, L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> IntoIterator
+    for &'a BiMap<L, R, LS, RS>
+{
+    type Item = (&'a L, &'a R);
+    type IntoIter = Iter<'a, L, R>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Iter<'a, L, R> {
+        self.iter()
+    }
+}
+
+/// Two maps are equal when they hold the same set of pairs, in any order.
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> PartialEq
+    for BiMap<L, R, LS, RS>
+{
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(left, right)| other.get_left(left) == Some(right))
+    }
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> Eq for BiMap<L, R, LS, RS> {}
+
+/// Formats a pair as `left <-> right` inside a map's `debug_set`.
+struct PairDebug<'a, L, R>(&'a L, &'a R);
+
+impl<L: fmt::Debug, R: fmt::Debug> fmt::Debug for PairDebug<'_, L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)?;
+        f.write_str(" <-> ")?;
+        self.1.fmt(f)
+    }
+}
+
+/// Formats as `{left <-> right, ...}`.
+impl<L: fmt::Debug, R: fmt::Debug, LS, RS> fmt::Debug for BiMap<L, R, LS, RS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(
+                self.left
+                    .values()
+                    .iter()
+                    .zip(self.right.values())
+                    .map(|(left, right)| PairDebug(left, right)),
+            )
+            .finish()
+    }
+}
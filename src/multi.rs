@@ -0,0 +1,257 @@
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::DefaultHashBuilder;
+
+/// A many-to-many bimap: every left value maps to a set of right values and
+/// every right value maps to a set of left values.
+pub struct BiMultiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    left_to_right: HashMap<L, HashSet<R, RS>, LS>,
+    right_to_left: HashMap<R, HashSet<L, LS>, RS>,
+    len: usize,
+}
+
+impl<L, R> BiMultiMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_hashers(DefaultHashBuilder::default(), DefaultHashBuilder::default())
+    }
+}
+
+impl<L, R, LS, RS> BiMultiMap<L, R, LS, RS> {
+    /// Creates an empty map that hashes left values with `left_hasher` and
+    /// right values with `right_hasher`, including inside the value sets.
+    #[inline(always)]
+    pub fn with_hashers(left_hasher: LS, right_hasher: RS) -> Self {
+        Self {
+            left_to_right: HashMap::with_hasher(left_hasher),
+            right_to_left: HashMap::with_hasher(right_hasher),
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn left_hasher(&self) -> &LS {
+        self.left_to_right.hasher()
+    }
+
+    #[inline(always)]
+    pub fn right_hasher(&self) -> &RS {
+        self.right_to_left.hasher()
+    }
+
+    /// Returns the number of pairs.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_left.clear();
+        self.len = 0;
+    }
+
+    /// Iterates over the distinct left values.
+    #[inline(always)]
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.left_to_right.keys()
+    }
+
+    /// Iterates over the distinct right values.
+    #[inline(always)]
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.right_to_left.keys()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left_to_right
+            .iter()
+            .flat_map(|(left, rights)| rights.iter().map(move |right| (left, right)))
+    }
+}
+
+impl<L, R, LS, RS> BiMultiMap<L, R, LS, RS>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    LS: BuildHasher + Clone,
+    RS: BuildHasher + Clone,
+{
+    /// Adds the pair, returning `false` if it was already present.
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> bool {
+        let right_hasher = self.right_to_left.hasher();
+        let rights = self
+            .left_to_right
+            .entry(left.clone())
+            .or_insert_with(|| HashSet::with_hasher(right_hasher.clone()));
+        if !rights.insert(right.clone()) {
+            return false;
+        }
+        let left_hasher = self.left_to_right.hasher();
+        self.right_to_left
+            .entry(right)
+            .or_insert_with(|| HashSet::with_hasher(left_hasher.clone()))
+            .insert(left);
+        self.len += 1;
+        true
+    }
+
+    /// Removes the pair, returning `false` if it was not present.
+    #[inline(always)]
+    pub fn remove_pair<QL, QR>(&mut self, left: &QL, right: &QR) -> bool
+    where
+        L: Borrow<QL>,
+        R: Borrow<QR>,
+        QL: ?Sized + Hash + Eq,
+        QR: ?Sized + Hash + Eq,
+    {
+        let Some(rights) = self.left_to_right.get_mut(left) else {
+            return false;
+        };
+        if !rights.remove(right) {
+            return false;
+        }
+        if rights.is_empty() {
+            self.left_to_right.remove(left);
+        }
+        let lefts = self.right_to_left.get_mut(right).unwrap();
+        lefts.remove(left);
+        if lefts.is_empty() {
+            self.right_to_left.remove(right);
+        }
+        self.len -= 1;
+        true
+    }
+
+    /// Removes every pair containing `left`, returning their right values.
+    #[inline(always)]
+    pub fn remove_left_all<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> HashSet<R, RS>
+    where
+        L: Borrow<Q>,
+    {
+        let Some((left, rights)) = self.left_to_right.remove_entry(left) else {
+            return HashSet::with_hasher(self.right_to_left.hasher().clone());
+        };
+        for right in &rights {
+            let lefts = self.right_to_left.get_mut(right).unwrap();
+            lefts.remove::<L>(&left);
+            if lefts.is_empty() {
+                self.right_to_left.remove(right);
+            }
+        }
+        self.len -= rights.len();
+        rights
+    }
+
+    /// Removes every pair containing `right`, returning their left values.
+    #[inline(always)]
+    pub fn remove_right_all<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> HashSet<L, LS>
+    where
+        R: Borrow<Q>,
+    {
+        let Some((right, lefts)) = self.right_to_left.remove_entry(right) else {
+            return HashSet::with_hasher(self.left_to_right.hasher().clone());
+        };
+        for left in &lefts {
+            let rights = self.left_to_right.get_mut(left).unwrap();
+            rights.remove::<R>(&right);
+            if rights.is_empty() {
+                self.left_to_right.remove(left);
+            }
+        }
+        self.len -= lefts.len();
+        lefts
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> impl Iterator<Item = &R>
+    where
+        L: Borrow<Q>,
+    {
+        self.left_to_right.get(left).into_iter().flatten()
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> impl Iterator<Item = &L>
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_left.get(right).into_iter().flatten()
+    }
+
+    /// Returns how many right values `left` maps to.
+    #[inline(always)]
+    pub fn left_count<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> usize
+    where
+        L: Borrow<Q>,
+    {
+        self.left_to_right.get(left).map_or(0, HashSet::len)
+    }
+
+    /// Returns how many left values `right` maps to.
+    #[inline(always)]
+    pub fn right_count<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> usize
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_left.get(right).map_or(0, HashSet::len)
+    }
+
+    #[inline(always)]
+    pub fn contains_pair<QL, QR>(&self, left: &QL, right: &QR) -> bool
+    where
+        L: Borrow<QL>,
+        R: Borrow<QR>,
+        QL: ?Sized + Hash + Eq,
+        QR: ?Sized + Hash + Eq,
+    {
+        self.left_to_right
+            .get(left)
+            .is_some_and(|rights| rights.contains(right))
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.left_to_right.contains_key(left)
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_left.contains_key(right)
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for BiMultiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hashers(LS::default(), RS::default())
+    }
+}
+
+impl<L: Clone, R: Clone, LS: Clone, RS: Clone> Clone for BiMultiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            left_to_right: self.left_to_right.clone(),
+            right_to_left: self.right_to_left.clone(),
+            len: self.len,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::ops::Deref;
+
+use crate::{BiMap, DefaultHashBuilder, InsertConflict, Overwritten};
+
+/// A change to an [`ObservedBiMap`], passed to every observer.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Event<'a, L, R> {
+    Inserted(&'a L, &'a R),
+    Removed(&'a L, &'a R),
+    /// A pair was replaced by a new pair sharing one of its values.
+    Rebound {
+        old: (&'a L, &'a R),
+        new: (&'a L, &'a R),
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Observer<L, R> = Box<dyn FnMut(&Event<'_, L, R>) + Send>;
+
+/// A [`BiMap`] that reports every change to registered observers. Reads go
+/// through `Deref`; writes go through the wrapper's own methods.
+pub struct ObservedBiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    map: BiMap<L, R, LS, RS>,
+    observers: Vec<(SubscriptionId, Observer<L, R>)>,
+    next_id: u64,
+}
+
+#[inline(always)]
+fn notify<L, R>(observers: &mut [(SubscriptionId, Observer<L, R>)], event: Event<'_, L, R>) {
+    for (_, observer) in observers.iter_mut() {
+        observer(&event);
+    }
+}
+
+impl<L, R> ObservedBiMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::from_map(BiMap::new())
+    }
+}
+
+impl<L, R, LS, RS> ObservedBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn from_map(map: BiMap<L, R, LS, RS>) -> Self {
+        Self {
+            map,
+            observers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_inner(self) -> BiMap<L, R, LS, RS> {
+        self.map
+    }
+
+    #[inline(always)]
+    pub fn subscribe<F>(&mut self, observer: F) -> SubscriptionId
+    where
+        F: FnMut(&Event<'_, L, R>) + Send + 'static,
+    {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.observers.push((id, Box::new(observer)));
+        id
+    }
+
+    /// Removes an observer, returning `false` if it was not registered.
+    #[inline(always)]
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.observers.len();
+        self.observers.retain(|(other, _)| *other != id);
+        self.observers.len() != len
+    }
+}
+
+impl<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher> ObservedBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let overwritten = self.map.insert(left, right);
+        let slot = match &overwritten {
+            Overwritten::Pair(..) => return overwritten,
+            Overwritten::Left(old_left, _) => self.map.find_left(old_left).unwrap(),
+            Overwritten::Right(_, old_right) => self.map.find_right(old_right).unwrap(),
+            Overwritten::Neither | Overwritten::Both(..) => self.map.len() - 1,
+        };
+        let new = self.map.pair(slot);
+        let event = match &overwritten {
+            Overwritten::Neither => Event::Inserted(new.0, new.1),
+            Overwritten::Left(left, right) | Overwritten::Right(left, right) => Event::Rebound {
+                old: (left, right),
+                new,
+            },
+            Overwritten::Both((left, right), (other_left, other_right)) => {
+                notify(&mut self.observers, Event::Removed(other_left, other_right));
+                Event::Rebound {
+                    old: (left, right),
+                    new,
+                }
+            }
+            Overwritten::Pair(..) => unreachable!(),
+        };
+        notify(&mut self.observers, event);
+        overwritten
+    }
+
+    #[inline(always)]
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        let left_hash = self.map.left.hash(&left);
+        let right_hash = self.map.right.hash(&right);
+        if self.map.left.find(left_hash, &left).is_some()
+            || self.map.right.find(right_hash, &right).is_some()
+        {
+            return self.map.try_insert(left, right);
+        }
+        self.map.push(left_hash, left, right_hash, right);
+        let (left, right) = self.map.pair(self.map.len() - 1);
+        notify(&mut self.observers, Event::Inserted(left, right));
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let (left, right) = self.map.remove_left(left)?;
+        notify(&mut self.observers, Event::Removed(&left, &right));
+        Some((left, right))
+    }
+
+    #[inline(always)]
+    pub fn remove_right<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+    {
+        let (left, right) = self.map.remove_right(right)?;
+        notify(&mut self.observers, Event::Removed(&left, &right));
+        Some((left, right))
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        for (left, right) in self.map.iter() {
+            notify(&mut self.observers, Event::Removed(left, right));
+        }
+        self.map.clear();
+    }
+
+    #[inline(always)]
+    pub fn retain<F: FnMut(&L, &R) -> bool>(&mut self, mut f: F) {
+        let observers = &mut self.observers;
+        self.map.retain(|left, right| {
+            let keep = f(left, right);
+            if !keep {
+                notify(observers, Event::Removed(left, right));
+            }
+            keep
+        });
+    }
+
+    /// Removes every pair, yielding them by value. Observers are notified of
+    /// every removal before the first pair is yielded.
+    #[inline(always)]
+    pub fn drain(&mut self) -> impl Iterator<Item = (L, R)> {
+        for (left, right) in self.map.iter() {
+            notify(&mut self.observers, Event::Removed(left, right));
+        }
+        self.map.drain()
+    }
+
+    /// Removes and yields the pairs for which `pred` returns `true`, notifying
+    /// observers as each pair is yielded.
+    #[inline(always)]
+    pub fn extract_if<F: FnMut(&L, &R) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> impl Iterator<Item = (L, R)> {
+        let observers = &mut self.observers;
+        self.map
+            .extract_if(pred)
+            .inspect(|(left, right)| notify(observers, Event::Removed(left, right)))
+    }
+}
+
+impl<L, R, LS, RS> Deref for ObservedBiMap<L, R, LS, RS> {
+    type Target = BiMap<L, R, LS, RS>;
+
+    #[inline(always)]
+    fn deref(&self) -> &BiMap<L, R, LS, RS> {
+        &self.map
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for ObservedBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::from_map(BiMap::default())
+    }
+}
@@ -0,0 +1,561 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::slice;
+
+use crate::{DefaultHashBuilder, InsertConflict};
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+type Entry<L, R> = Arc<(L, R)>;
+
+/// A node of a hash array mapped trie. Children are stored densely, in the
+/// order of their bits in `bitmap`.
+struct Branch<L, R> {
+    bitmap: u32,
+    children: Vec<Child<L, R>>,
+}
+
+enum Child<L, R> {
+    Leaf(u64, Entry<L, R>),
+    /// Entries whose full hashes are equal.
+    Collision(u64, Vec<Entry<L, R>>),
+    Branch(Arc<Branch<L, R>>),
+}
+
+impl<L, R> Clone for Branch<L, R> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            bitmap: self.bitmap,
+            children: self.children.clone(),
+        }
+    }
+}
+
+impl<L, R> Clone for Child<L, R> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        match self {
+            Child::Leaf(hash, entry) => Child::Leaf(*hash, entry.clone()),
+            Child::Collision(hash, entries) => Child::Collision(*hash, entries.clone()),
+            Child::Branch(branch) => Child::Branch(branch.clone()),
+        }
+    }
+}
+
+#[inline(always)]
+fn empty<L, R>() -> Arc<Branch<L, R>> {
+    Arc::new(Branch {
+        bitmap: 0,
+        children: Vec::new(),
+    })
+}
+
+/// Returns the bit for `hash` at `shift` and the position of its child.
+#[inline(always)]
+fn locate(bitmap: u32, hash: u64, shift: u32) -> (u32, usize) {
+    let bit = 1 << ((hash >> shift) & MASK);
+    (bit, (bitmap & (bit - 1)).count_ones() as usize)
+}
+
+#[inline(always)]
+fn left<L, R>(pair: &(L, R)) -> &L {
+    &pair.0
+}
+
+#[inline(always)]
+fn right<L, R>(pair: &(L, R)) -> &R {
+    &pair.1
+}
+
+fn find<'a, L, R, K, Q>(
+    mut node: &'a Branch<L, R>,
+    hash: u64,
+    key: &Q,
+    project: fn(&(L, R)) -> &K,
+) -> Option<&'a Entry<L, R>>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Eq,
+{
+    let mut shift = 0;
+    loop {
+        let (bit, index) = locate(node.bitmap, hash, shift);
+        if node.bitmap & bit == 0 {
+            return None;
+        }
+        match &node.children[index] {
+            Child::Leaf(h, entry) => {
+                return (*h == hash && project(entry).borrow() == key).then_some(entry);
+            }
+            Child::Collision(h, entries) => {
+                if *h != hash {
+                    return None;
+                }
+                return entries.iter().find(|e| project(e).borrow() == key);
+            }
+            Child::Branch(branch) => {
+                node = branch;
+                shift += BITS;
+            }
+        }
+    }
+}
+
+/// Inserts an entry whose key is not yet in the trie.
+fn insert<L, R>(node: &mut Arc<Branch<L, R>>, shift: u32, hash: u64, entry: Entry<L, R>) {
+    let node = Arc::make_mut(node);
+    let (bit, index) = locate(node.bitmap, hash, shift);
+    if node.bitmap & bit == 0 {
+        node.bitmap |= bit;
+        node.children.insert(index, Child::Leaf(hash, entry));
+        return;
+    }
+    let child = &mut node.children[index];
+    match child {
+        Child::Branch(branch) => insert(branch, shift + BITS, hash, entry),
+        Child::Collision(h, entries) if *h == hash => entries.push(entry),
+        Child::Leaf(h, old) if *h == hash => {
+            *child = Child::Collision(hash, vec![old.clone(), entry])
+        }
+        Child::Leaf(h, _) | Child::Collision(h, _) => {
+            // Push the existing child one level down, next to the new entry.
+            let (old_bit, _) = locate(0, *h, shift + BITS);
+            let mut branch = Arc::new(Branch {
+                bitmap: old_bit,
+                children: vec![child.clone()],
+            });
+            insert(&mut branch, shift + BITS, hash, entry);
+            *child = Child::Branch(branch);
+        }
+    }
+}
+
+/// Removes the entry matching `is_target`, which must be in the trie.
+fn remove<L, R>(
+    node: &mut Arc<Branch<L, R>>,
+    shift: u32,
+    hash: u64,
+    is_target: &impl Fn(&Entry<L, R>) -> bool,
+) -> Entry<L, R> {
+    let node = Arc::make_mut(node);
+    let (bit, index) = locate(node.bitmap, hash, shift);
+    let child = &mut node.children[index];
+    match child {
+        Child::Leaf(..) => {
+            node.bitmap &= !bit;
+            let Child::Leaf(_, entry) = node.children.remove(index) else {
+                unreachable!()
+            };
+            entry
+        }
+        Child::Collision(_, entries) => {
+            let position = entries.iter().position(is_target).unwrap();
+            let entry = entries.swap_remove(position);
+            if entries.len() == 1 {
+                *child = Child::Leaf(hash, entries.pop().unwrap());
+            }
+            entry
+        }
+        Child::Branch(branch) => {
+            let entry = remove(branch, shift + BITS, hash, is_target);
+            // A branch left holding a single entry is folded into its parent.
+            if let [only @ (Child::Leaf(..) | Child::Collision(..))] = &branch.children[..] {
+                *child = only.clone();
+            }
+            entry
+        }
+    }
+}
+
+/// An immutable bimap whose updates return new versions sharing structure
+/// with the old one. Both sides are hash array mapped tries over the same
+/// reference-counted pairs, so `clone` is O(1) and updates are O(log n).
+/// Not available on targets without atomic pointers, where `Arc` is missing.
+pub struct PersistentBiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    left: Arc<Branch<L, R>>,
+    right: Arc<Branch<L, R>>,
+    len: usize,
+    left_hasher: LS,
+    right_hasher: RS,
+}
+
+impl<L, R> PersistentBiMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_hashers(DefaultHashBuilder::default(), DefaultHashBuilder::default())
+    }
+}
+
+impl<L, R, LS, RS> PersistentBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn with_hashers(left_hasher: LS, right_hasher: RS) -> Self {
+        Self {
+            left: empty(),
+            right: empty(),
+            len: 0,
+            left_hasher,
+            right_hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub fn left_hasher(&self) -> &LS {
+        &self.left_hasher
+    }
+
+    #[inline(always)]
+    pub fn right_hasher(&self) -> &RS {
+        &self.right_hasher
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the pairs in an unspecified order.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        Entries {
+            stack: vec![self.left.children.iter()],
+            collision: [].iter(),
+        }
+        .map(|pair| (&pair.0, &pair.1))
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.iter().map(|(left, _)| left)
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.iter().map(|(_, right)| right)
+    }
+}
+
+impl<L, R, LS, RS> PersistentBiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher + Clone,
+    RS: BuildHasher + Clone,
+{
+    /// Returns a new version in which `left` and `right` are bound to each
+    /// other, dropping any pairs that contained either of them.
+    pub fn insert(&self, left: L, right: R) -> Self {
+        let left_hash = self.left_hasher.hash_one(&left);
+        let right_hash = self.right_hasher.hash_one(&right);
+        let by_left = find(&self.left, left_hash, &left, self::left);
+        let by_right = find(&self.right, right_hash, &right, self::right);
+        let mut next = self.clone();
+        if let Some(entry) = by_left {
+            next.unlink(entry, left_hash, self.right_hasher.hash_one(&entry.1));
+        }
+        if let Some(entry) = by_right {
+            // Skip the pair if it also contained `left` and is already gone.
+            if !by_left.is_some_and(|by_left| Arc::ptr_eq(by_left, entry)) {
+                next.unlink(entry, self.left_hasher.hash_one(&entry.0), right_hash);
+            }
+        }
+        next.link(Arc::new((left, right)), left_hash, right_hash);
+        next
+    }
+
+    /// Like [`PersistentBiMap::insert`], but refuses to displace any pair.
+    pub fn try_insert(&self, left: L, right: R) -> Result<Self, InsertConflict<'_, L, R>> {
+        let left_hash = self.left_hasher.hash_one(&left);
+        let right_hash = self.right_hasher.hash_one(&right);
+        let by_left = find(&self.left, left_hash, &left, self::left);
+        let by_right = find(&self.right, right_hash, &right, self::right);
+        if by_left.is_some() || by_right.is_some() {
+            return Err(InsertConflict {
+                left,
+                right,
+                existing_left: by_left.map(|entry| (&entry.0, &entry.1)),
+                existing_right: by_right.map(|entry| (&entry.0, &entry.1)),
+            });
+        }
+
+        let mut next = self.clone();
+        next.link(Arc::new((left, right)), left_hash, right_hash);
+        Ok(next)
+    }
+
+    /// Returns a new version without the pair containing `left`.
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Self
+    where
+        L: Borrow<Q>,
+    {
+        let left_hash = self.left_hasher.hash_one(left);
+        let mut next = self.clone();
+        if let Some(entry) = find(&self.left, left_hash, left, self::left) {
+            next.unlink(entry, left_hash, self.right_hasher.hash_one(&entry.1));
+        }
+        next
+    }
+
+    /// Returns a new version without the pair containing `right`.
+    pub fn remove_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Self
+    where
+        R: Borrow<Q>,
+    {
+        let right_hash = self.right_hasher.hash_one(right);
+        let mut next = self.clone();
+        if let Some(entry) = find(&self.right, right_hash, right, self::right) {
+            next.unlink(entry, self.left_hasher.hash_one(&entry.0), right_hash);
+        }
+        next
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        let hash = self.left_hasher.hash_one(left);
+        find(&self.left, hash, left, self::left).map(|entry| &entry.1)
+    }
+
+    #[inline(always)]
+    pub fn get_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+    {
+        let hash = self.right_hasher.hash_one(right);
+        find(&self.right, hash, right, self::right).map(|entry| &entry.0)
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.get_left(left).is_some()
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.get_right(right).is_some()
+    }
+
+    #[inline(always)]
+    fn link(&mut self, entry: Entry<L, R>, left_hash: u64, right_hash: u64) {
+        insert(&mut self.left, 0, left_hash, entry.clone());
+        insert(&mut self.right, 0, right_hash, entry);
+        self.len += 1;
+    }
+
+    #[inline(always)]
+    fn unlink(&mut self, entry: &Entry<L, R>, left_hash: u64, right_hash: u64) {
+        let is_target = |e: &Entry<L, R>| Arc::ptr_eq(e, entry);
+        remove(&mut self.left, 0, left_hash, &is_target);
+        remove(&mut self.right, 0, right_hash, &is_target);
+        self.len -= 1;
+    }
+}
+
+/// Walks a trie depth first.
+struct Entries<'a, L, R> {
+    stack: Vec<slice::Iter<'a, Child<L, R>>>,
+    collision: slice::Iter<'a, Entry<L, R>>,
+}
+
+impl<'a, L, R> Iterator for Entries<'a, L, R> {
+    type Item = &'a (L, R);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry) = self.collision.next() {
+            return Some(entry);
+        }
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(Child::Leaf(_, entry)) => return Some(entry),
+                Some(Child::Collision(_, entries)) => {
+                    self.collision = entries.iter();
+                    return self.collision.next().map(|entry| &**entry);
+                }
+                Some(Child::Branch(branch)) => self.stack.push(branch.children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for PersistentBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hashers(LS::default(), RS::default())
+    }
+}
+
+impl<L, R, LS: Clone, RS: Clone> Clone for PersistentBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            left: self.left.clone(),
+            right: self.right.clone(),
+            len: self.len,
+            left_hasher: self.left_hasher.clone(),
+            right_hasher: self.right_hasher.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+    use std::collections::HashMap;
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Hashes `n` to one of six values whose only differing bits sit in the
+    /// first and eighth trie levels, so equal hashes become `Collision`s and
+    /// every other pair of keys needs a chain of branches to separate them.
+    #[derive(Clone, Default)]
+    struct Degenerate;
+
+    struct DegenerateHasher(u64);
+
+    impl Hasher for DegenerateHasher {
+        fn finish(&self) -> u64 {
+            (self.0 % 2) | (self.0 % 3) << 35
+        }
+
+        fn write(&mut self, _: &[u8]) {
+            unreachable!()
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n;
+        }
+    }
+
+    impl BuildHasher for Degenerate {
+        type Hasher = DegenerateHasher;
+
+        fn build_hasher(&self) -> DegenerateHasher {
+            DegenerateHasher(0)
+        }
+    }
+
+    type Map = PersistentBiMap<u64, u64, Degenerate, Degenerate>;
+
+    #[derive(Default)]
+    struct Shape {
+        collisions: usize,
+        depth: u32,
+    }
+
+    /// Checks the trie invariants below `node` and returns how many entries
+    /// it holds.
+    fn check_branch(
+        node: &Branch<u64, u64>,
+        shift: u32,
+        is_root: bool,
+        shape: &mut Shape,
+    ) -> usize {
+        assert_eq!(node.bitmap.count_ones() as usize, node.children.len());
+        if !is_root {
+            // Removal folds a branch holding a single entry into its parent.
+            assert!(node.children.len() > 1 || matches!(node.children[..], [Child::Branch(_)]));
+        }
+        shape.depth = shape.depth.max(shift / BITS);
+        let mut bits = node.bitmap;
+        let mut entries = 0;
+        for child in &node.children {
+            let bit = bits.trailing_zeros() as u64;
+            bits &= bits - 1;
+            let hash = match child {
+                Child::Leaf(hash, _) => {
+                    entries += 1;
+                    *hash
+                }
+                Child::Collision(hash, collided) => {
+                    assert!(collided.len() > 1);
+                    shape.collisions += 1;
+                    entries += collided.len();
+                    *hash
+                }
+                Child::Branch(branch) => {
+                    entries += check_branch(branch, shift + BITS, false, shape);
+                    continue;
+                }
+            };
+            assert_eq!((hash >> shift) & MASK, bit);
+        }
+        entries
+    }
+
+    fn check(map: &Map, model: &HashMap<u64, u64>, shape: &mut Shape) {
+        assert_eq!(check_branch(&map.left, 0, true, shape), model.len());
+        assert_eq!(check_branch(&map.right, 0, true, shape), model.len());
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.iter().count(), model.len());
+        for (left, right) in model {
+            assert_eq!(map.get_left(left), Some(right));
+            assert_eq!(map.get_right(right), Some(left));
+        }
+    }
+
+    #[test]
+    fn degenerate_hashes_match_a_model() {
+        const KEYS: u64 = 24;
+        let mut map = Map::default();
+        let mut model = HashMap::new();
+        let mut versions: Vec<(Map, HashMap<u64, u64>)> = Vec::new();
+        let mut shape = Shape::default();
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        for step in 0..4_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let (left, right) = (state % KEYS, (state >> 32) % KEYS);
+            map = match state >> 62 {
+                0 | 1 => {
+                    if let Some(old_right) = model.remove(&left) {
+                        model.retain(|_, r| *r != old_right);
+                    }
+                    model.retain(|_, r| *r != right);
+                    model.insert(left, right);
+                    map.insert(left, right)
+                }
+                2 => {
+                    model.remove(&left);
+                    map.remove_left(&left)
+                }
+                _ => {
+                    model.retain(|_, r| *r != right);
+                    map.remove_right(&right)
+                }
+            };
+            check(&map, &model, &mut shape);
+            if step % 100 == 0 {
+                versions.push((map.clone(), model.clone()));
+            }
+        }
+
+        assert!(shape.collisions > 0);
+        assert!(shape.depth >= 7);
+        // Later updates must not have leaked into earlier versions.
+        for (version, model) in &versions {
+            check(version, model, &mut Shape::default());
+        }
+    }
+}
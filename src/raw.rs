@@ -0,0 +1,311 @@
+use alloc::vec::{self, Vec};
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+
+use hashbrown::HashTable;
+
+/// One side of a bimap: the values in slot order plus a hash index from value
+/// to slot. Both sides of a map keep their slots aligned, so slot `i` of the
+/// left side and slot `i` of the right side form a pair.
+#[derive(Clone)]
+pub(crate) struct Side<T, S> {
+    values: Vec<T>,
+    index: HashTable<usize>,
+    hasher: S,
+}
+
+impl<T, S> Side<T, S> {
+    #[inline(always)]
+    pub(crate) fn with_hasher(hasher: S) -> Self {
+        Self {
+            values: Vec::new(),
+            index: HashTable::new(),
+            hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            index: HashTable::with_capacity(capacity),
+            hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    #[inline(always)]
+    pub(crate) fn get(&self, slot: usize) -> &T {
+        &self.values[slot]
+    }
+
+    #[inline(always)]
+    pub(crate) fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub(crate) fn into_values(self) -> Vec<T> {
+        self.values
+    }
+
+    #[inline(always)]
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+        self.index.clear();
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher> Side<T, S> {
+    #[inline(always)]
+    pub(crate) fn hash<Q: ?Sized + Hash>(&self, value: &Q) -> u64 {
+        self.hasher.hash_one(value)
+    }
+
+    #[inline(always)]
+    pub(crate) fn find<Q: ?Sized + Eq>(&self, hash: u64, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+    {
+        self.index
+            .find(hash, |&i| self.values[i].borrow() == value)
+            .copied()
+    }
+
+    #[inline(always)]
+    pub(crate) fn push(&mut self, hash: u64, value: T) {
+        let Side {
+            values,
+            index,
+            hasher,
+        } = self;
+        values.push(value);
+        index.insert_unique(hash, values.len() - 1, |&i| hasher.hash_one(&values[i]));
+    }
+
+    /// Removes the value in `slot`, moving the last value into its place.
+    #[inline(always)]
+    pub(crate) fn swap_remove(&mut self, slot: usize) -> T {
+        self.unindex(slot);
+        let value = self.values.swap_remove(slot);
+        let moved = self.values.len();
+        if slot < moved {
+            let hash = self.hash(&self.values[slot]);
+            *self.index.find_mut(hash, |&i| i == moved).unwrap() = slot;
+        }
+        value
+    }
+
+    /// Removes the value in `slot`, shifting every later value down by one.
+    #[inline(always)]
+    pub(crate) fn shift_remove(&mut self, slot: usize) -> T {
+        self.unindex(slot);
+        let value = self.values.remove(slot);
+        for i in self.index.iter_mut() {
+            if *i > slot {
+                *i -= 1;
+            }
+        }
+        value
+    }
+
+    /// Inserts `value` into `slot`, moving the value previously there to the
+    /// end. This is the exact inverse of [`Side::swap_remove`].
+    #[inline(always)]
+    pub(crate) fn restore(&mut self, slot: usize, hash: u64, value: T) {
+        self.push(hash, value);
+        let last = self.values.len() - 1;
+        if slot < last {
+            let moved = self.hash(&self.values[slot]);
+            let a = self.index.find_bucket_index(moved, |&i| i == slot).unwrap();
+            let b = self.index.find_bucket_index(hash, |&i| i == last).unwrap();
+            *self.index.get_bucket_mut(a).unwrap() = last;
+            *self.index.get_bucket_mut(b).unwrap() = slot;
+            self.values.swap(slot, last);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn drain(&mut self) -> vec::Drain<'_, T> {
+        self.index.clear();
+        self.values.drain(..)
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_values(&mut self) -> Vec<T> {
+        self.index.clear();
+        mem::take(&mut self.values)
+    }
+
+    /// Replaces the whole side with `values`, which must not contain duplicates.
+    #[inline(always)]
+    pub(crate) fn set_values(&mut self, values: Vec<T>) {
+        self.index.clear();
+        self.values = values;
+        let Side {
+            values,
+            index,
+            hasher,
+        } = self;
+        for (slot, value) in values.iter().enumerate() {
+            index.insert_unique(hasher.hash_one(value), slot, |&i| {
+                hasher.hash_one(&values[i])
+            });
+        }
+    }
+
+    /// Replaces the value in `slot` with a value that may hash differently.
+    #[inline(always)]
+    pub(crate) fn replace(&mut self, slot: usize, hash: u64, value: T) -> T {
+        self.unindex(slot);
+        let old = mem::replace(&mut self.values[slot], value);
+        let Side {
+            values,
+            index,
+            hasher,
+        } = self;
+        index.insert_unique(hash, slot, |&i| hasher.hash_one(&values[i]));
+        old
+    }
+
+    /// Replaces the value in `slot` with a value equal to it.
+    #[inline(always)]
+    pub(crate) fn replace_equal(&mut self, slot: usize, value: T) -> T {
+        mem::replace(&mut self.values[slot], value)
+    }
+
+    #[inline(always)]
+    fn unindex(&mut self, slot: usize) {
+        let hash = self.hash(&self.values[slot]);
+        self.index
+            .find_entry(hash, |&i| i == slot)
+            .unwrap()
+            .remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::DefaultHashBuilder;
+
+    fn side(values: impl IntoIterator<Item = u32>) -> Side<u32, DefaultHashBuilder> {
+        let mut side = Side::with_hasher(DefaultHashBuilder::default());
+        for value in values {
+            side.push(side.hash(&value), value);
+        }
+        side
+    }
+
+    /// Checks that the index maps every value to its slot and nothing else.
+    fn check(side: &Side<u32, DefaultHashBuilder>) {
+        assert_eq!(side.index.len(), side.len());
+        for (slot, value) in side.values().iter().enumerate() {
+            assert_eq!(side.find(side.hash(value), value), Some(slot));
+        }
+        let mut slots: Vec<usize> = side.index.iter().copied().collect();
+        slots.sort_unstable();
+        assert!(slots.iter().copied().eq(0..side.len()));
+    }
+
+    #[test]
+    fn swap_remove_reindexes_the_moved_value() {
+        let mut side = side(0..8);
+        assert_eq!(side.swap_remove(2), 2);
+        assert_eq!(side.values(), [0, 1, 7, 3, 4, 5, 6]);
+        check(&side);
+        assert_eq!(side.swap_remove(6), 6);
+        check(&side);
+        assert_eq!(side.find(side.hash(&2), &2), None);
+    }
+
+    #[test]
+    fn restore_reverses_swap_remove() {
+        let mut side = side(0..8);
+        for slot in [3, 0, 5, 4] {
+            let before = side.values().to_vec();
+            let value = side.swap_remove(slot);
+            side.restore(slot, side.hash(&value), value);
+            assert_eq!(side.values(), before);
+            check(&side);
+        }
+    }
+
+    #[test]
+    fn shift_remove_keeps_order() {
+        let mut side = side(0..8);
+        assert_eq!(side.shift_remove(2), 2);
+        assert_eq!(side.values(), [0, 1, 3, 4, 5, 6, 7]);
+        check(&side);
+        assert_eq!(side.shift_remove(0), 0);
+        check(&side);
+    }
+
+    #[test]
+    fn replace_moves_the_value_to_its_new_hash() {
+        let mut side = side(0..8);
+        assert_eq!(side.replace(3, side.hash(&100), 100), 3);
+        assert_eq!(side.values(), [0, 1, 2, 100, 4, 5, 6, 7]);
+        assert_eq!(side.find(side.hash(&3), &3), None);
+        check(&side);
+        assert_eq!(side.replace_equal(3, 100), 100);
+        check(&side);
+    }
+
+    #[test]
+    fn set_values_rebuilds_the_index() {
+        let mut side = side(0..8);
+        side.set_values([9, 3, 5].into());
+        check(&side);
+        assert_eq!(side.find(side.hash(&0), &0), None);
+    }
+
+    #[test]
+    fn random_updates_keep_the_index_consistent() {
+        let mut side = side([]);
+        let mut model: Vec<u32> = Vec::new();
+        let mut state = 0x853c_49e6_748f_ea9b_u64;
+        for _ in 0..5_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let value = (state % 64) as u32;
+            let at = (state >> 32) as usize % (model.len() + 1);
+            match (state >> 60, model.contains(&value)) {
+                (0..=5, false) => {
+                    side.push(side.hash(&value), value);
+                    model.push(value);
+                }
+                (6..=8, false) if at < model.len() => {
+                    model[at] = value;
+                    side.replace(at, side.hash(&value), value);
+                }
+                (9..=11, _) if at < model.len() => {
+                    assert_eq!(side.swap_remove(at), model.swap_remove(at));
+                }
+                (12..=13, _) if at < model.len() => {
+                    assert_eq!(side.shift_remove(at), model.remove(at));
+                }
+                (14..=15, _) if at < model.len() => {
+                    let value = side.swap_remove(at);
+                    side.restore(at, side.hash(&value), value);
+                }
+                _ => {}
+            }
+            assert_eq!(side.values(), model);
+            check(&side);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+#[cfg(target_has_atomic = "ptr")]
+use crate::BTreeBiMap;
+use crate::{BiMap, IndexBiMap, InsertConflict};
+
+/// Maps that can be rebuilt from a sequence of pairs without overwriting.
+trait FromPairs<L, R>: Sized {
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn try_insert_pair(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>>;
+}
+
+struct PairsVisitor<M, L, R> {
+    marker: PhantomData<(M, L, R)>,
+}
+
+impl<'de, M, L, R> Visitor<'de> for PairsVisitor<M, L, R>
+where
+    M: FromPairs<L, R>,
+    L: Deserialize<'de>,
+    R: Deserialize<'de>,
+{
+    type Value = M;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (left, right) pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<M, A::Error> {
+        // Don't trust the size hint with more than a modest preallocation.
+        let mut map = M::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        let mut index = 0;
+        while let Some((left, right)) = seq.next_element()? {
+            if let Err(conflict) = map.try_insert_pair(left, right) {
+                return Err(A::Error::custom(format_args!("pair {index}: {conflict}")));
+            }
+            index += 1;
+        }
+        Ok(map)
+    }
+}
+
+fn deserialize_pairs<'de, D, M, L, R>(deserializer: D) -> Result<M, D::Error>
+where
+    D: Deserializer<'de>,
+    M: FromPairs<L, R>,
+    L: Deserialize<'de>,
+    R: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(PairsVisitor {
+        marker: PhantomData,
+    })
+}
+
+impl<L, R, LS, RS> FromPairs<L, R> for BiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher + Default,
+    RS: BuildHasher + Default,
+{
+    fn with_capacity(capacity: usize) -> Self {
+        BiMap::with_capacity_and_hashers(capacity, LS::default(), RS::default())
+    }
+
+    fn try_insert_pair(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        self.try_insert(left, right)
+    }
+}
+
+impl<L, R, LS, RS> FromPairs<L, R> for IndexBiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher + Default,
+    RS: BuildHasher + Default,
+{
+    fn with_capacity(capacity: usize) -> Self {
+        IndexBiMap::with_capacity_and_hashers(capacity, LS::default(), RS::default())
+    }
+
+    fn try_insert_pair(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        self.try_insert(left, right)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<L: Ord, R: Ord> FromPairs<L, R> for BTreeBiMap<L, R> {
+    fn with_capacity(_: usize) -> Self {
+        BTreeBiMap::new()
+    }
+
+    fn try_insert_pair(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        self.try_insert(left, right)
+    }
+}
+
+impl<L: Serialize, R: Serialize, LS, RS> Serialize for BiMap<L, R, LS, RS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.left.values().iter().zip(self.right.values()))
+    }
+}
+
+impl<L: Serialize, R: Serialize, LS, RS> Serialize for IndexBiMap<L, R, LS, RS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<L, R> Serialize for BTreeBiMap<L, R>
+where
+    L: Serialize,
+    R: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, L, R, LS, RS> Deserialize<'de> for BiMap<L, R, LS, RS>
+where
+    L: Deserialize<'de> + Eq + Hash,
+    R: Deserialize<'de> + Eq + Hash,
+    LS: BuildHasher + Default,
+    RS: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pairs(deserializer)
+    }
+}
+
+impl<'de, L, R, LS, RS> Deserialize<'de> for IndexBiMap<L, R, LS, RS>
+where
+    L: Deserialize<'de> + Eq + Hash,
+    R: Deserialize<'de> + Eq + Hash,
+    LS: BuildHasher + Default,
+    RS: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pairs(deserializer)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'de, L, R> Deserialize<'de> for BTreeBiMap<L, R>
+where
+    L: Deserialize<'de> + Ord,
+    R: Deserialize<'de> + Ord,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_pairs(deserializer)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use std::string::{String, ToString};
+
+    use super::*;
+
+    const PAIRS: &str = r#"[["a",1],["b",2],["c",3]]"#;
+
+    fn rejects<M: for<'de> Deserialize<'de>>(json: &str, message: &str) {
+        let error = serde_json::from_str::<M>(json).err().unwrap().to_string();
+        assert!(error.starts_with(message), "{error}");
+    }
+
+    fn rejects_duplicates<M: for<'de> Deserialize<'de>>() {
+        rejects::<M>(
+            r#"[["a",1],["b",2],["a",3]]"#,
+            "pair 2: left value is already bound",
+        );
+        rejects::<M>(
+            r#"[["a",1],["b",2],["c",1]]"#,
+            "pair 2: right value is already bound",
+        );
+        rejects::<M>(
+            r#"[["a",1],["a",1]]"#,
+            "pair 1: left and right values are already bound",
+        );
+    }
+
+    #[test]
+    fn bimap_round_trips_and_rejects_duplicates() {
+        let map: BiMap<String, u32> = serde_json::from_str(PAIRS).unwrap();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BiMap<String, u32>>(&json).unwrap(),
+            map
+        );
+        assert_eq!(map.len(), 3);
+        rejects_duplicates::<BiMap<String, u32>>();
+    }
+
+    #[test]
+    fn index_bimap_round_trips_in_order_and_rejects_duplicates() {
+        let map: IndexBiMap<String, u32> = serde_json::from_str(PAIRS).unwrap();
+        assert_eq!(serde_json::to_string(&map).unwrap(), PAIRS);
+        rejects_duplicates::<IndexBiMap<String, u32>>();
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn btree_bimap_round_trips_sorted_and_rejects_duplicates() {
+        let map: BTreeBiMap<String, u32> =
+            serde_json::from_str(r#"[["c",3],["a",1],["b",2]]"#).unwrap();
+        assert_eq!(serde_json::to_string(&map).unwrap(), PAIRS);
+        assert!(map.left_values().map(String::as_str).eq(["a", "b", "c"]));
+        rejects_duplicates::<BTreeBiMap<String, u32>>();
+    }
+}
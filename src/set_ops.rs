@@ -0,0 +1,153 @@
+use core::error::Error;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+use crate::BiMap;
+
+/// Decides what a merge does with pairs that bind a value differently in the
+/// two inputs, such as `a <-> 1` on one side and `a <-> 2` on the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Keep the conflicting pairs of `self`.
+    KeepSelf,
+    /// Keep the conflicting pairs of `other`.
+    KeepOther,
+    /// Drop the conflicting pairs of both inputs.
+    Drop,
+    /// Fail with the first conflict found.
+    Error,
+}
+
+/// The error returned by a merge under [`ConflictPolicy::Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict<L, R> {
+    /// The pair of `self` that conflicts.
+    pub ours: (L, R),
+    /// The pair of `other` that holds the same left value, if any.
+    pub theirs_by_left: Option<(L, R)>,
+    /// The pair of `other` that holds the same right value, if any.
+    pub theirs_by_right: Option<(L, R)>,
+}
+
+impl<L, R> fmt::Display for MergeConflict<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("pair is bound differently in the other map")
+    }
+}
+
+impl<L: fmt::Debug, R: fmt::Debug> Error for MergeConflict<L, R> {}
+
+impl<L, R, LS, RS> BiMap<L, R, LS, RS>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    LS: BuildHasher + Clone,
+    RS: BuildHasher + Clone,
+{
+    /// Returns every pair of either map, resolving conflicts with `policy`.
+    #[inline(always)]
+    pub fn union_with(
+        &self,
+        other: &Self,
+        policy: ConflictPolicy,
+    ) -> Result<Self, MergeConflict<L, R>> {
+        self.merge(other, policy, true)
+    }
+
+    /// Returns the pairs found in exactly one of the maps, resolving conflicts
+    /// with `policy`.
+    #[inline(always)]
+    pub fn symmetric_difference(
+        &self,
+        other: &Self,
+        policy: ConflictPolicy,
+    ) -> Result<Self, MergeConflict<L, R>> {
+        self.merge(other, policy, false)
+    }
+
+    /// Returns the pairs found in both maps.
+    #[inline(always)]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.filtered(|left, right| other.get_left(left) == Some(right))
+    }
+
+    /// Returns the pairs of `self` that are not in `other`.
+    #[inline(always)]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.filtered(|left, right| other.get_left(left) != Some(right))
+    }
+
+    #[inline(always)]
+    fn merge(
+        &self,
+        other: &Self,
+        policy: ConflictPolicy,
+        keep_shared: bool,
+    ) -> Result<Self, MergeConflict<L, R>> {
+        // A pair conflicts when the other map binds one of its values to
+        // something else. Conflicts are symmetric, so checking the pairs of
+        // `self` is enough to find one for `ConflictPolicy::Error`.
+        let mut merged = self.empty_clone();
+        for (left, right) in self.iter() {
+            if other.get_left(left) == Some(right) {
+                if keep_shared {
+                    merged.push_new(left.clone(), right.clone());
+                }
+            } else if !other.conflicts_with(left, right) {
+                merged.push_new(left.clone(), right.clone());
+            } else {
+                match policy {
+                    ConflictPolicy::KeepSelf => merged.push_new(left.clone(), right.clone()),
+                    ConflictPolicy::KeepOther | ConflictPolicy::Drop => {}
+                    ConflictPolicy::Error => return Err(other.conflict(left, right)),
+                }
+            }
+        }
+        for (left, right) in other.iter() {
+            if self.get_left(left) == Some(right) {
+                continue;
+            }
+            if !self.conflicts_with(left, right) || policy == ConflictPolicy::KeepOther {
+                merged.push_new(left.clone(), right.clone());
+            }
+        }
+        Ok(merged)
+    }
+
+    #[inline(always)]
+    fn filtered<F: FnMut(&L, &R) -> bool>(&self, mut keep: F) -> Self {
+        let mut filtered = self.empty_clone();
+        for (left, right) in self.iter() {
+            if keep(left, right) {
+                filtered.push_new(left.clone(), right.clone());
+            }
+        }
+        filtered
+    }
+
+    #[inline(always)]
+    fn empty_clone(&self) -> Self {
+        BiMap::with_hashers(self.left_hasher().clone(), self.right_hasher().clone())
+    }
+
+    #[inline(always)]
+    fn conflicts_with(&self, left: &L, right: &R) -> bool {
+        matches!(self.get_left(left), Some(bound) if bound != right)
+            || matches!(self.get_right(right), Some(bound) if bound != left)
+    }
+
+    #[inline(always)]
+    fn conflict(&self, left: &L, right: &R) -> MergeConflict<L, R> {
+        let by_left = self
+            .get_left(left)
+            .map(|theirs| (left.clone(), theirs.clone()));
+        let by_right = self
+            .get_right(right)
+            .map(|theirs| (theirs.clone(), right.clone()));
+        MergeConflict {
+            ours: (left.clone(), right.clone()),
+            theirs_by_left: by_left,
+            theirs_by_right: by_right,
+        }
+    }
+}
@@ -0,0 +1,459 @@
+/// A type whose literals can be keys of a [`StaticBiMap`]. Lookups take
+/// `&Self::Lookup`, which lets string tables be queried with any `&str`.
+pub trait StaticKey: 'static {
+    type Lookup: ?Sized;
+
+    /// Returns the bytes a [`PerfectHash`] locates `key` by, or `None` if keys
+    /// of this type are found by the `match` that [`bimap!`] generates.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn lookup_bytes(key: &Self::Lookup) -> Option<&[u8]> {
+        let _ = key;
+        None
+    }
+
+    /// Like [`StaticKey::lookup_bytes`], for a key stored in the map.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn key_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl StaticKey for &'static str {
+    type Lookup = str;
+
+    #[inline(always)]
+    fn lookup_bytes(key: &str) -> Option<&[u8]> {
+        Some(key.as_bytes())
+    }
+
+    #[inline(always)]
+    fn key_bytes(&self) -> Option<&[u8]> {
+        Some(self.as_bytes())
+    }
+}
+
+macro_rules! impl_static_key {
+    ($($ty:ty),*) => {
+        $(impl StaticKey for $ty {
+            type Lookup = $ty;
+        })*
+    };
+}
+
+impl_static_key!(
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/// How many keys share a bucket of a [`PerfectHash`] on average.
+const BUCKET_SIZE: usize = 4;
+
+/// How many slots a [`PerfectHash`] has per empty slot. Spare slots make the
+/// last buckets much cheaper to place.
+const SLOTS_PER_SPARE: usize = 4;
+
+/// How many seeds [`PerfectHash::new`] tries before giving up.
+const SEEDS: u64 = 64;
+
+/// An entry of a [`PerfectHash`]. Bucket `i` keeps its displacement in slot
+/// `i`, and every slot keeps the position of the key that hashes to it, if
+/// any; there are never more buckets than slots.
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct Slot {
+    d1: u32,
+    d2: u32,
+    key: u32,
+}
+
+/// A perfect hash from a fixed set of strings to their positions,
+/// built at compile time by hash and displace: keys are grouped into buckets
+/// by one part of their hash, and each bucket gets the displacement that sends
+/// all of its keys to free slots. A lookup hashes the key once and reads two
+/// slots.
+#[doc(hidden)]
+pub struct PerfectHash<S: ?Sized = [Slot]> {
+    seed: u64,
+    slots: S,
+}
+
+/// FNV-1a followed by the MurmurHash3 finalizer, so that every output bit
+/// depends on every input bit.
+#[inline(always)]
+const fn hash(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = seed ^ 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Splits a hash into a bucket selector and the two values a displacement
+/// combines.
+#[inline(always)]
+const fn split(hash: u64) -> (u32, u32, u32) {
+    const MASK: u64 = (1 << 21) - 1;
+    (
+        (hash >> 42) as u32,
+        (hash >> 21 & MASK) as u32,
+        (hash & MASK) as u32,
+    )
+}
+
+#[inline(always)]
+const fn buckets(slots: usize) -> usize {
+    (slots - slots / SLOTS_PER_SPARE).div_ceil(BUCKET_SIZE)
+}
+
+#[inline(always)]
+const fn displace(f1: u32, f2: u32, d1: u32, d2: u32, slots: usize) -> usize {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2) as usize % slots
+}
+
+impl PerfectHash {
+    /// Returns how many slots the table for the literals in `tokens` needs:
+    /// a few more than there are keys for string literals, and none for other
+    /// literals, which are looked up by `match`.
+    pub const fn slots_for(tokens: &[&str]) -> usize {
+        match tokens[0].as_bytes()[0] {
+            b'"' | b'r' => tokens.len() + tokens.len() / (SLOTS_PER_SPARE - 1),
+            _ => 0,
+        }
+    }
+
+    /// Returns the position of the only key that may equal the one `bytes`
+    /// belong to. The caller compares the two.
+    #[inline(always)]
+    fn position(&self, bytes: &[u8]) -> usize {
+        let (g, f1, f2) = split(hash(bytes, self.seed));
+        let slots = self.slots.len();
+        let Slot { d1, d2, .. } = self.slots[g as usize % buckets(slots)];
+        self.slots[displace(f1, f2, d1, d2, slots)].key as usize
+    }
+}
+
+impl<const N: usize> PerfectHash<[Slot; N]> {
+    const EMPTY: Slot = Slot {
+        d1: 0,
+        d2: 0,
+        key: 0,
+    };
+
+    /// Builds the table for `keys` with the number of slots
+    /// [`PerfectHash::slots_for`] gave, or an empty one if that is zero.
+    /// Fails to compile if a key repeats.
+    pub const fn new(keys: &[&str]) -> Self {
+        if N == 0 {
+            return Self {
+                seed: 0,
+                slots: [Self::EMPTY; N],
+            };
+        }
+        assert!(keys.len() <= N && buckets(N) <= keys.len());
+        let mut seed = 0;
+        while seed < SEEDS {
+            if let Some(slots) = Self::build(keys, seed) {
+                return Self { seed, slots };
+            }
+            seed += 1;
+        }
+        panic!("no perfect hash found for the keys of bimap!");
+    }
+
+    /// Tries to place every key with the given seed.
+    const fn build(keys: &[&str], seed: u64) -> Option<[Slot; N]> {
+        let buckets = buckets(N);
+        // Bucket every key, then lay the keys out grouped by bucket.
+        let mut hashes = [(0, 0, 0); N];
+        let mut sizes = [0; N];
+        let mut largest = 0;
+        let mut key = 0;
+        while key < keys.len() {
+            let (g, f1, f2) = split(hash(keys[key].as_bytes(), seed));
+            let bucket = g as usize % buckets;
+            hashes[key] = (bucket, f1, f2);
+            sizes[bucket] += 1;
+            if sizes[bucket] > largest {
+                largest = sizes[bucket];
+            }
+            key += 1;
+        }
+        let mut starts = [0; N];
+        let mut bucket = 1;
+        while bucket < buckets {
+            starts[bucket] = starts[bucket - 1] + sizes[bucket - 1];
+            bucket += 1;
+        }
+        let mut members = [0; N];
+        let mut filled = [0; N];
+        key = 0;
+        while key < keys.len() {
+            let bucket = hashes[key].0;
+            members[starts[bucket] + filled[bucket]] = key;
+            filled[bucket] += 1;
+            key += 1;
+        }
+
+        let mut slots = [Self::EMPTY; N];
+        let mut taken = [false; N];
+        // `marks[slot] == trial` means the current trial already sent a key
+        // of the bucket there.
+        let mut marks = [0u64; N];
+        let mut trial = 0;
+        // Place the largest buckets first, while most slots are still free.
+        let mut size = largest;
+        while size > 0 {
+            let mut bucket = 0;
+            while bucket < buckets {
+                if sizes[bucket] != size {
+                    bucket += 1;
+                    continue;
+                }
+                let (start, end) = (starts[bucket], starts[bucket] + size);
+                // Keys with equal hashes can never be told apart by a
+                // displacement: either they repeat, or the seed is unlucky.
+                let mut i = start;
+                while i < end {
+                    let mut j = i + 1;
+                    while j < end {
+                        let (a, b) = (hashes[members[i]], hashes[members[j]]);
+                        if a.1 == b.1 && a.2 == b.2 {
+                            let (a, b) = (keys[members[i]].as_bytes(), keys[members[j]].as_bytes());
+                            if a.len() == b.len() {
+                                let mut k = 0;
+                                while k < a.len() && a[k] == b[k] {
+                                    k += 1;
+                                }
+                                assert!(k < a.len(), "a key of bimap! repeats");
+                            }
+                            return None;
+                        }
+                        j += 1;
+                    }
+                    i += 1;
+                }
+
+                let mut placed = false;
+                let mut d1 = 0;
+                'search: while d1 < N as u32 {
+                    let mut d2 = 0;
+                    while d2 < N as u32 {
+                        trial += 1;
+                        let mut i = start;
+                        while i < end {
+                            let (_, f1, f2) = hashes[members[i]];
+                            let slot = displace(f1, f2, d1, d2, N);
+                            if taken[slot] || marks[slot] == trial {
+                                break;
+                            }
+                            marks[slot] = trial;
+                            i += 1;
+                        }
+                        if i == end {
+                            slots[bucket].d1 = d1;
+                            slots[bucket].d2 = d2;
+                            let mut i = start;
+                            while i < end {
+                                let (_, f1, f2) = hashes[members[i]];
+                                let slot = displace(f1, f2, d1, d2, N);
+                                taken[slot] = true;
+                                slots[slot].key = members[i] as u32;
+                                i += 1;
+                            }
+                            placed = true;
+                            break 'search;
+                        }
+                        d2 += 1;
+                    }
+                    d1 += 1;
+                }
+                if !placed {
+                    return None;
+                }
+                bucket += 1;
+            }
+            size -= 1;
+        }
+        Some(slots)
+    }
+}
+
+/// An immutable, allocation-free bimap built at compile time by [`bimap!`].
+/// String keys are located through a perfect hash computed by the compiler,
+/// so a lookup hashes the key once and compares it with a single pair. Other
+/// keys are looked up by `match` expressions generated by the macro, which
+/// compile to jump tables or short decision trees.
+pub struct StaticBiMap<L: StaticKey, R: StaticKey> {
+    pairs: &'static [(L, R)],
+    left_hash: &'static PerfectHash,
+    right_hash: &'static PerfectHash,
+    by_left: fn(&L::Lookup) -> Option<&'static R>,
+    by_right: fn(&R::Lookup) -> Option<&'static L>,
+}
+
+impl<L: StaticKey, R: StaticKey> StaticBiMap<L, R> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub const fn from_parts(
+        pairs: &'static [(L, R)],
+        left_hash: &'static PerfectHash,
+        right_hash: &'static PerfectHash,
+        by_left: fn(&L::Lookup) -> Option<&'static R>,
+        by_right: fn(&R::Lookup) -> Option<&'static L>,
+    ) -> Self {
+        Self {
+            pairs,
+            left_hash,
+            right_hash,
+            by_left,
+            by_right,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_left(&self, left: &L::Lookup) -> Option<&'static R> {
+        let Some(bytes) = L::lookup_bytes(left) else {
+            return (self.by_left)(left);
+        };
+        let pairs: &'static [(L, R)] = self.pairs;
+        let (candidate, right) = &pairs[self.left_hash.position(bytes)];
+        (candidate.key_bytes() == Some(bytes)).then_some(right)
+    }
+
+    #[inline(always)]
+    pub fn get_right(&self, right: &R::Lookup) -> Option<&'static L> {
+        let Some(bytes) = R::lookup_bytes(right) else {
+            return (self.by_right)(right);
+        };
+        let pairs: &'static [(L, R)] = self.pairs;
+        let (left, candidate) = &pairs[self.right_hash.position(bytes)];
+        (candidate.key_bytes() == Some(bytes)).then_some(left)
+    }
+
+    #[inline(always)]
+    pub fn contains_left(&self, left: &L::Lookup) -> bool {
+        self.get_left(left).is_some()
+    }
+
+    #[inline(always)]
+    pub fn contains_right(&self, right: &R::Lookup) -> bool {
+        self.get_right(right).is_some()
+    }
+
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Iterates over the pairs in the order they were written.
+    #[inline(always)]
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&'static L, &'static R)> + ExactSizeIterator {
+        self.pairs.iter().map(|(left, right)| (left, right))
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl DoubleEndedIterator<Item = &'static L> + ExactSizeIterator {
+        self.pairs.iter().map(|(left, _)| left)
+    }
+
+    #[inline(always)]
+    pub fn right_values(&self) -> impl DoubleEndedIterator<Item = &'static R> + ExactSizeIterator {
+        self.pairs.iter().map(|(_, right)| right)
+    }
+}
+
+impl<L: StaticKey, R: StaticKey> Clone for StaticBiMap<L, R> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L: StaticKey, R: StaticKey> Copy for StaticBiMap<L, R> {}
+
+/// Builds a [`StaticBiMap`] from literal pairs. A value that appears twice on
+/// either side is a compile error, so the table is one-to-one by construction.
+///
+/// ```
+/// let codes = bimap::bimap! { "ok" <=> 200, "not found" <=> 404 };
+/// assert_eq!(codes.get_left("ok"), Some(&200));
+/// assert_eq!(codes.get_right(&404), Some(&"not found"));
+/// ```
+///
+/// ```compile_fail
+/// let codes = bimap::bimap! { "ok" <=> 200, "fine" <=> 200 };
+/// ```
+#[macro_export]
+macro_rules! bimap {
+    ($($left:literal <=> $right:literal),+ $(,)?) => {{
+        // These also reject repeated values, even where the lint is allowed.
+        #[deny(unreachable_patterns)]
+        let by_left = |left: &_| match left {
+            $($left => ::core::option::Option::Some(&$right),)+
+            _ => ::core::option::Option::None,
+        };
+        #[deny(unreachable_patterns)]
+        let by_right = |right: &_| match right {
+            $($right => ::core::option::Option::Some(&$left),)+
+            _ => ::core::option::Option::None,
+        };
+        const LEFT_SLOTS: usize = $crate::PerfectHash::slots_for(&[$(stringify!($left)),+]);
+        const RIGHT_SLOTS: usize = $crate::PerfectHash::slots_for(&[$(stringify!($right)),+]);
+        $crate::StaticBiMap::from_parts(
+            &[$(($left, $right)),+],
+            &const { $crate::PerfectHash::<[_; LEFT_SLOTS]>::new(&[$(concat!($left)),+]) },
+            &const { $crate::PerfectHash::<[_; RIGHT_SLOTS]>::new(&[$(concat!($right)),+]) },
+            by_left,
+            by_right,
+        )
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::string::String;
+    use std::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn perfect_hash_finds_every_key() {
+        const KEYS: usize = 600;
+        const SLOTS: usize = KEYS + KEYS / (SLOTS_PER_SPARE - 1);
+        assert_eq!(PerfectHash::slots_for(&["\"key\""; KEYS]), SLOTS);
+        let owned: Vec<String> = (0..KEYS).map(|i| format!("key{i}")).collect();
+        let keys: Vec<&str> = owned.iter().map(String::as_str).collect();
+        let table: &PerfectHash = &PerfectHash::<[Slot; SLOTS]>::new(&keys);
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(table.position(key.as_bytes()), i);
+        }
+    }
+
+    #[test]
+    fn non_strings_get_no_slots() {
+        assert_eq!(PerfectHash::slots_for(&["1", "2"]), 0);
+        assert_eq!(PerfectHash::slots_for(&["'a'"]), 0);
+        assert_eq!(PerfectHash::slots_for(&["r\"a\"", "\"b\""]), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "a key of bimap! repeats")]
+    fn perfect_hash_rejects_repeats() {
+        PerfectHash::<[Slot; 4]>::new(&["a", "b", "a"]);
+    }
+}
@@ -0,0 +1,229 @@
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::DefaultHashBuilder;
+
+/// A many-to-one map that also indexes the reverse direction: every left value
+/// maps to exactly one right value, and every right value to the group of left
+/// values mapped to it.
+pub struct SurjectiveMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    left_to_right: HashMap<L, R, LS>,
+    right_to_lefts: HashMap<R, HashSet<L, LS>, RS>,
+}
+
+impl<L, R> SurjectiveMap<L, R> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_hashers(DefaultHashBuilder::default(), DefaultHashBuilder::default())
+    }
+}
+
+impl<L, R, LS, RS> SurjectiveMap<L, R, LS, RS> {
+    /// Creates an empty map that hashes left values with `left_hasher`,
+    /// including inside the groups, and right values with `right_hasher`.
+    #[inline(always)]
+    pub fn with_hashers(left_hasher: LS, right_hasher: RS) -> Self {
+        Self {
+            left_to_right: HashMap::with_hasher(left_hasher),
+            right_to_lefts: HashMap::with_hasher(right_hasher),
+        }
+    }
+
+    #[inline(always)]
+    pub fn left_hasher(&self) -> &LS {
+        self.left_to_right.hasher()
+    }
+
+    #[inline(always)]
+    pub fn right_hasher(&self) -> &RS {
+        self.right_to_lefts.hasher()
+    }
+
+    /// Returns the number of left values.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.left_to_right.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.left_to_right.clear();
+        self.right_to_lefts.clear();
+    }
+
+    #[inline(always)]
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.left_to_right.keys()
+    }
+
+    /// Iterates over the distinct right values.
+    #[inline(always)]
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.right_to_lefts.keys()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left_to_right.iter()
+    }
+}
+
+impl<L, R, LS, RS> SurjectiveMap<L, R, LS, RS>
+where
+    L: Eq + Hash + Clone,
+    R: Eq + Hash + Clone,
+    LS: BuildHasher + Clone,
+    RS: BuildHasher,
+{
+    /// Maps `left` to `right`, returning the right value it was mapped to before.
+    #[inline(always)]
+    pub fn insert(&mut self, left: L, right: R) -> Option<R> {
+        match self.left_to_right.get_mut(&left) {
+            Some(old_right) => {
+                let old_right = mem::replace(old_right, right.clone());
+                self.detach(&left, &old_right);
+                self.attach(left, right);
+                Some(old_right)
+            }
+            None => {
+                self.left_to_right.insert(left.clone(), right.clone());
+                self.attach(left, right);
+                None
+            }
+        }
+    }
+
+    /// Moves an existing `left` to `right`, returning its old right value, or
+    /// hands `right` back if `left` is not in the map.
+    #[inline(always)]
+    pub fn reassign<Q: ?Sized + Hash + Eq>(&mut self, left: &Q, right: R) -> Result<R, R>
+    where
+        L: Borrow<Q>,
+    {
+        let Some(left) = self
+            .left_to_right
+            .get_key_value(left)
+            .map(|(left, _)| left.clone())
+        else {
+            return Err(right);
+        };
+        let old_right = self
+            .left_to_right
+            .insert(left.clone(), right.clone())
+            .unwrap();
+        if old_right != right {
+            self.detach(&left, &old_right);
+            self.attach(left, right);
+        }
+        Ok(old_right)
+    }
+
+    #[inline(always)]
+    pub fn get_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+    {
+        self.left_to_right.get(left)
+    }
+
+    #[inline(always)]
+    pub fn lefts_of<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> impl Iterator<Item = &L>
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_lefts.get(right).into_iter().flatten()
+    }
+
+    #[inline(always)]
+    pub fn lefts_count<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> usize
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_lefts.get(right).map_or(0, HashSet::len)
+    }
+
+    #[inline(always)]
+    pub fn contains_left<Q: ?Sized + Hash + Eq>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.left_to_right.contains_key(left)
+    }
+
+    #[inline(always)]
+    pub fn contains_right<Q: ?Sized + Hash + Eq>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+    {
+        self.right_to_lefts.contains_key(right)
+    }
+
+    #[inline(always)]
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+    {
+        let (left, right) = self.left_to_right.remove_entry(left)?;
+        self.detach(&left, &right);
+        Some((left, right))
+    }
+
+    /// Removes `right` together with every left value mapped to it.
+    #[inline(always)]
+    pub fn remove_right_all<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> HashSet<L, LS>
+    where
+        R: Borrow<Q>,
+    {
+        let lefts = self
+            .right_to_lefts
+            .remove(right)
+            .unwrap_or_else(|| HashSet::with_hasher(self.left_to_right.hasher().clone()));
+        for left in &lefts {
+            self.left_to_right.remove(left);
+        }
+        lefts
+    }
+
+    #[inline(always)]
+    fn attach(&mut self, left: L, right: R) {
+        let left_hasher = self.left_to_right.hasher();
+        self.right_to_lefts
+            .entry(right)
+            .or_insert_with(|| HashSet::with_hasher(left_hasher.clone()))
+            .insert(left);
+    }
+
+    #[inline(always)]
+    fn detach(&mut self, left: &L, right: &R) {
+        let lefts = self.right_to_lefts.get_mut(right).unwrap();
+        lefts.remove(left);
+        if lefts.is_empty() {
+            self.right_to_lefts.remove(right);
+        }
+    }
+}
+
+impl<L, R, LS: Default, RS: Default> Default for SurjectiveMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hashers(LS::default(), RS::default())
+    }
+}
+
+impl<L: Clone, R: Clone, LS: Clone, RS: Clone> Clone for SurjectiveMap<L, R, LS, RS> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            left_to_right: self.left_to_right.clone(),
+            right_to_lefts: self.right_to_lefts.clone(),
+        }
+    }
+}
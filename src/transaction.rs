@@ -0,0 +1,456 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+use core::ops::Deref;
+
+use crate::{BiMap, DefaultHashBuilder, InsertConflict, Overwritten};
+
+/// A primitive change to the slots of a map. Applying one yields its exact
+/// inverse, so a list of them can be replayed backwards to undo a batch.
+enum Op<L, R> {
+    /// Swap-removes the pair in the slot.
+    Remove(usize),
+    /// Puts a pair back into the slot, undoing a `Remove`.
+    Restore(usize, L, R),
+    /// Replaces the pair in the slot.
+    Replace(usize, L, R),
+}
+
+impl<L: Eq + Hash, R: Eq + Hash> Op<L, R> {
+    #[inline(always)]
+    fn apply<LS: BuildHasher, RS: BuildHasher>(self, map: &mut BiMap<L, R, LS, RS>) -> Self {
+        match self {
+            Op::Remove(slot) => {
+                let (left, right) = map.swap_remove(slot);
+                Op::Restore(slot, left, right)
+            }
+            Op::Restore(slot, left, right) => {
+                map.restore(slot, left, right);
+                Op::Remove(slot)
+            }
+            Op::Replace(slot, left, right) => {
+                let (left, right) = map.replace(slot, left, right);
+                Op::Replace(slot, left, right)
+            }
+        }
+    }
+}
+
+impl<L, R> Op<L, R> {
+    #[inline(always)]
+    fn pair(&self) -> (&L, &R) {
+        match self {
+            Op::Restore(_, left, right) | Op::Replace(_, left, right) => (left, right),
+            Op::Remove(_) => unreachable!(),
+        }
+    }
+}
+
+/// Undoes a journal, returning the journal that redoes it.
+#[inline(always)]
+fn revert<L: Eq + Hash, R: Eq + Hash, LS: BuildHasher, RS: BuildHasher>(
+    map: &mut BiMap<L, R, LS, RS>,
+    journal: Vec<Op<L, R>>,
+) -> Vec<Op<L, R>> {
+    journal.into_iter().rev().map(|op| op.apply(map)).collect()
+}
+
+/// A batch of changes to a [`BiMap`], created by [`BiMap::transaction`].
+/// Reads go through `Deref` and see the batch's changes so far. Unless the
+/// batch commits, every change is rolled back when the transaction is dropped.
+pub struct Transaction<'a, L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    map: &'a mut BiMap<L, R, LS, RS>,
+    journal: Vec<Op<L, R>>,
+}
+
+impl<L, R, LS, RS> Transaction<'_, L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    #[inline(always)]
+    fn run(&mut self, op: Op<L, R>) {
+        let inverse = op.apply(self.map);
+        self.journal.push(inverse);
+    }
+
+    /// Like [`BiMap::insert`], but the displaced pairs stay owned by the
+    /// transaction so they can be restored on rollback.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<&L, &R> {
+        let by_left = self.map.find_left(&left);
+        let by_right = self.map.find_right(&right);
+        match (by_left, by_right) {
+            (None, None) => {
+                self.run(Op::Restore(self.map.len(), left, right));
+                Overwritten::Neither
+            }
+            (Some(slot), None) | (None, Some(slot)) => {
+                self.run(Op::Replace(slot, left, right));
+                let (l, r) = self.journal.last().unwrap().pair();
+                if by_right.is_none() {
+                    Overwritten::Left(l, r)
+                } else {
+                    Overwritten::Right(l, r)
+                }
+            }
+            (Some(i), Some(j)) if i == j => {
+                self.run(Op::Replace(i, left, right));
+                let (l, r) = self.journal.last().unwrap().pair();
+                Overwritten::Pair(l, r)
+            }
+            (Some(i), Some(j)) => {
+                self.run(Op::Remove(i.max(j)));
+                self.run(Op::Remove(i.min(j)));
+                self.run(Op::Restore(self.map.len(), left, right));
+                let [high, low, _] = &self.journal[self.journal.len() - 3..] else {
+                    unreachable!()
+                };
+                if i > j {
+                    Overwritten::Both(high.pair(), low.pair())
+                } else {
+                    Overwritten::Both(low.pair(), high.pair())
+                }
+            }
+        }
+    }
+
+    /// Like [`BiMap::try_insert`].
+    pub fn try_insert(&mut self, left: L, right: R) -> Result<(), InsertConflict<'_, L, R>> {
+        if self.map.find_left(&left).is_some() || self.map.find_right(&right).is_some() {
+            return self.map.try_insert(left, right);
+        }
+        self.run(Op::Restore(self.map.len(), left, right));
+        Ok(())
+    }
+
+    pub fn remove_left<Q: ?Sized + Hash + Eq>(&mut self, left: &Q) -> Option<(&L, &R)>
+    where
+        L: Borrow<Q>,
+    {
+        let slot = self.map.find_left(left)?;
+        self.run(Op::Remove(slot));
+        Some(self.journal.last().unwrap().pair())
+    }
+
+    pub fn remove_right<Q: ?Sized + Hash + Eq>(&mut self, right: &Q) -> Option<(&L, &R)>
+    where
+        R: Borrow<Q>,
+    {
+        let slot = self.map.find_right(right)?;
+        self.run(Op::Remove(slot));
+        Some(self.journal.last().unwrap().pair())
+    }
+
+    /// Rolls back every change made so far without ending the transaction.
+    pub fn rollback(&mut self) {
+        let journal = mem::take(&mut self.journal);
+        revert(self.map, journal);
+    }
+}
+
+impl<L, R, LS, RS> Deref for Transaction<'_, L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    type Target = BiMap<L, R, LS, RS>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<L, R, LS, RS> Drop for Transaction<'_, L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    fn drop(&mut self) {
+        self.rollback();
+    }
+}
+
+/// Runs `f` in a transaction, returning its result and, if it committed, the
+/// journal that undoes it.
+fn transact<L, R, LS, RS, T, E, F>(
+    map: &mut BiMap<L, R, LS, RS>,
+    f: F,
+) -> (Result<T, E>, Vec<Op<L, R>>)
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+    F: FnOnce(&mut Transaction<'_, L, R, LS, RS>) -> Result<T, E>,
+{
+    let mut tx = Transaction {
+        map,
+        journal: Vec::new(),
+    };
+    let result = f(&mut tx);
+    let journal = match result {
+        Ok(_) => mem::take(&mut tx.journal),
+        Err(_) => Vec::new(),
+    };
+    (result, journal)
+}
+
+impl<L, R, LS, RS> BiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    /// Applies the changes made by `f` atomically: if `f` returns `Err` or
+    /// panics, the map is restored to exactly its prior state, including any
+    /// pairs evicted by inserts along the way.
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_, L, R, LS, RS>) -> Result<T, E>,
+    {
+        transact(self, f).0
+    }
+}
+
+/// A [`BiMap`] that keeps the last `limit` committed transactions so they can
+/// be undone and redone. Reads go through `Deref`; writes go through
+/// [`UndoBiMap::transaction`].
+pub struct UndoBiMap<L, R, LS = DefaultHashBuilder, RS = DefaultHashBuilder> {
+    map: BiMap<L, R, LS, RS>,
+    undo: VecDeque<Vec<Op<L, R>>>,
+    redo: Vec<Vec<Op<L, R>>>,
+    limit: usize,
+}
+
+impl<L, R> UndoBiMap<L, R> {
+    #[inline(always)]
+    pub fn new(limit: usize) -> Self {
+        Self::from_map(BiMap::new(), limit)
+    }
+}
+
+impl<L, R, LS, RS> UndoBiMap<L, R, LS, RS> {
+    #[inline(always)]
+    pub fn from_map(map: BiMap<L, R, LS, RS>, limit: usize) -> Self {
+        Self {
+            map,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_inner(self) -> BiMap<L, R, LS, RS> {
+        self.map
+    }
+
+    #[inline(always)]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    #[inline(always)]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+impl<L, R, LS, RS> UndoBiMap<L, R, LS, RS>
+where
+    L: Eq + Hash,
+    R: Eq + Hash,
+    LS: BuildHasher,
+    RS: BuildHasher,
+{
+    /// Like [`BiMap::transaction`]; a committed transaction that changed the
+    /// map becomes the most recent undo step and discards the redo history.
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_, L, R, LS, RS>) -> Result<T, E>,
+    {
+        let (result, journal) = transact(&mut self.map, f);
+        if !journal.is_empty() {
+            self.redo.clear();
+            if self.limit > 0 {
+                if self.undo.len() == self.limit {
+                    self.undo.pop_front();
+                }
+                self.undo.push_back(journal);
+            }
+        }
+        result
+    }
+
+    /// Undoes the most recent committed transaction. Returns `false` if there
+    /// was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(journal) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(revert(&mut self.map, journal));
+        true
+    }
+
+    /// Redoes the most recently undone transaction. Returns `false` if there
+    /// was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(journal) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(revert(&mut self.map, journal));
+        true
+    }
+}
+
+impl<L, R, LS, RS> Deref for UndoBiMap<L, R, LS, RS> {
+    type Target = BiMap<L, R, LS, RS>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::vec;
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn pairs(map: &BiMap<u8, char>) -> Vec<(u8, char)> {
+        map.iter().map(|(left, right)| (*left, *right)).collect()
+    }
+
+    fn base() -> BiMap<u8, char> {
+        [(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]
+            .into_iter()
+            .collect()
+    }
+
+    /// Runs every kind of change, including one insert that evicts two pairs.
+    fn churn(tx: &mut Transaction<'_, u8, char, DefaultHashBuilder, DefaultHashBuilder>) {
+        tx.insert(1, 'c');
+        tx.insert(5, 'e');
+        tx.insert(2, 'z');
+        tx.insert(5, 'e');
+        tx.remove_right(&'d');
+        assert!(tx.try_insert(6, 'e').is_err());
+        tx.try_insert(7, 'g').unwrap();
+    }
+
+    #[test]
+    fn commit_applies_every_change() {
+        let mut map = base();
+        let mut expected = base();
+        map.transaction(|tx| {
+            churn(tx);
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        for (left, right) in [(1, 'c'), (5, 'e'), (2, 'z'), (5, 'e'), (7, 'g')] {
+            expected.insert(left, right);
+        }
+        expected.remove_right(&'d');
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn err_restores_the_exact_prior_state() {
+        let mut map = base();
+        let before = pairs(&map);
+        let result = map.transaction(|tx| {
+            churn(tx);
+            Err::<(), _>("abort")
+        });
+        assert_eq!(result, Err("abort"));
+        assert_eq!(pairs(&map), before);
+    }
+
+    #[test]
+    fn panic_restores_the_exact_prior_state() {
+        let mut map = base();
+        let before = pairs(&map);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            map.transaction(|tx| -> Result<(), ()> {
+                churn(tx);
+                panic!("boom")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(pairs(&map), before);
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut map = UndoBiMap::from_map(base(), 8);
+        let mut states = vec![pairs(&map)];
+        for step in 0..3 {
+            map.transaction(|tx| {
+                tx.insert(step + 1, 'c');
+                tx.insert(10 + step, 'x');
+                tx.remove_left(&4);
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+            states.push(pairs(&map));
+        }
+
+        for state in states.iter().rev().skip(1) {
+            assert!(map.undo());
+            assert_eq!(&pairs(&map), state);
+        }
+        assert!(!map.undo());
+        for state in &states[1..] {
+            assert!(map.redo());
+            assert_eq!(&pairs(&map), state);
+        }
+        assert!(!map.redo());
+    }
+
+    #[test]
+    fn empty_commit_is_not_an_undo_step() {
+        let mut map = UndoBiMap::from_map(base(), 8);
+        map.transaction(|tx| {
+            tx.insert(9, 'i');
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        assert!(map.undo());
+
+        map.transaction(|_| Ok::<_, ()>(())).unwrap();
+        assert!(map.can_redo());
+        assert!(map.redo());
+        assert!(map.undo());
+        assert!(!map.can_undo());
+    }
+}
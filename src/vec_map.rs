@@ -0,0 +1,187 @@
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use hashbrown::HashTable;
+
+use crate::DefaultHashBuilder;
+
+/// A bimap between dense indices and values. Looking up a value by index is an
+/// array access; only the value side is hashed. Removed indices are reused by
+/// later pushes, most recently freed first.
+#[derive(Clone)]
+pub struct VecBiMap<L, S = DefaultHashBuilder> {
+    slots: Vec<Option<L>>,
+    index: HashTable<usize>,
+    free: Vec<usize>,
+    hasher: S,
+}
+
+impl<L> VecBiMap<L> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<L, S> VecBiMap<L, S> {
+    #[inline(always)]
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            slots: Vec::new(),
+            index: HashTable::new(),
+            free: Vec::new(),
+            hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            index: HashTable::with_capacity(capacity),
+            free: Vec::new(),
+            hasher,
+        }
+    }
+
+    #[inline(always)]
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    #[inline(always)]
+    pub fn get_by_index(&self, index: usize) -> Option<&L> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn contains_index(&self, index: usize) -> bool {
+        self.get_by_index(index).is_some()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns one more than the highest index that has ever been handed out.
+    #[inline(always)]
+    pub fn index_bound(&self) -> usize {
+        self.slots.len()
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.index.clear();
+        self.free.clear();
+    }
+
+    /// Iterates over the occupied indices and their values in index order.
+    #[inline(always)]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &L)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| Some((index, slot.as_ref()?)))
+    }
+
+    #[inline(always)]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &L> {
+        self.slots.iter().flatten()
+    }
+}
+
+impl<L: Eq + Hash, S: BuildHasher> VecBiMap<L, S> {
+    /// Returns the index of `value`, storing it in a free slot first if it is
+    /// not present yet.
+    #[inline(always)]
+    pub fn push(&mut self, value: L) -> usize {
+        let hash = self.hasher.hash_one(&value);
+        if let Some(index) = self.find(hash, &value) {
+            return index;
+        }
+
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Some(value));
+                self.slots.len() - 1
+            }
+        };
+        let VecBiMap { slots, hasher, .. } = self;
+        self.index.insert_unique(hash, index, |&i| {
+            hasher.hash_one(slots[i].as_ref().unwrap())
+        });
+        index
+    }
+
+    #[inline(always)]
+    pub fn index_of<Q: ?Sized + Hash + Eq>(&self, value: &Q) -> Option<usize>
+    where
+        L: Borrow<Q>,
+    {
+        self.find(self.hasher.hash_one(value), value)
+    }
+
+    #[inline(always)]
+    pub fn contains_value<Q: ?Sized + Hash + Eq>(&self, value: &Q) -> bool
+    where
+        L: Borrow<Q>,
+    {
+        self.index_of(value).is_some()
+    }
+
+    /// Removes the value at `index`, freeing the index for reuse.
+    #[inline(always)]
+    pub fn remove_by_index(&mut self, index: usize) -> Option<L> {
+        let value = self.slots.get_mut(index)?.take()?;
+        self.index
+            .find_entry(self.hasher.hash_one(&value), |&i| i == index)
+            .unwrap()
+            .remove();
+        self.free.push(index);
+        Some(value)
+    }
+
+    /// Removes `value`, returning the index it was stored at.
+    #[inline(always)]
+    pub fn remove_value<Q: ?Sized + Hash + Eq>(&mut self, value: &Q) -> Option<(usize, L)>
+    where
+        L: Borrow<Q>,
+    {
+        let index = self.index_of(value)?;
+        Some((index, self.remove_by_index(index).unwrap()))
+    }
+
+    #[inline(always)]
+    fn find<Q: ?Sized + Eq>(&self, hash: u64, value: &Q) -> Option<usize>
+    where
+        L: Borrow<Q>,
+    {
+        self.index
+            .find(hash, |&i| self.slots[i].as_ref().unwrap().borrow() == value)
+            .copied()
+    }
+}
+
+impl<L, S: Default> Default for VecBiMap<L, S> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}